@@ -0,0 +1,320 @@
+use rand::Rng;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::GameConfig;
+use crate::controller::*;
+use crate::game_state::*;
+use crate::missile::*;
+use crate::spaceship::*;
+
+/// The actions tried by the **DodgingController**, by order of preference:
+/// advance if possible, otherwise wait, and retreat as a last resort.
+const PREFERRED_ACTIONS: [SpaceshipAction; 3] = [
+    SpaceshipAction::Upward,
+    SpaceshipAction::Idle,
+    SpaceshipAction::Downward,
+];
+
+/// The difficulty of the CPU opponent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+/// All the **Difficulty** values, from the easiest to the hardest.
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Normal,
+    Difficulty::Hard,
+    Difficulty::Insane,
+];
+
+impl Difficulty {
+    /// The amount of ticks during which the CPU keeps playing its
+    /// previous decision before deciding again.
+    pub fn reaction_delay(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 1,
+            Difficulty::Insane => 0,
+        }
+    }
+
+    /// The amount of ticks the CPU predicts the missiles' trajectories.
+    pub fn lookahead(&self) -> usize {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Normal => 4,
+            Difficulty::Hard => 8,
+            Difficulty::Insane => 12,
+        }
+    }
+
+    /// The probability for every decision of the CPU to be a random action.
+    pub fn mistake_rate(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.1,
+            Difficulty::Normal => 0.08,
+            Difficulty::Hard => 0.02,
+            Difficulty::Insane => 0.0,
+        }
+    }
+
+    /// The next harder difficulty, wrapping to the easiest one.
+    pub fn next(&self) -> Difficulty {
+        let i = DIFFICULTIES.iter().position(|d| d == self).unwrap_or(0);
+        DIFFICULTIES[(i + 1) % DIFFICULTIES.len()]
+    }
+
+    /// The next easier difficulty, wrapping to the hardest one.
+    pub fn previous(&self) -> Difficulty {
+        let i = DIFFICULTIES.iter().position(|d| d == self).unwrap_or(0);
+        DIFFICULTIES[(i + DIFFICULTIES.len() - 1) % DIFFICULTIES.len()]
+    }
+
+    /// The name of the difficulty, as displayed to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Insane => "Insane",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            "insane" => Ok(Difficulty::Insane),
+            _ => Err(format!(
+                "unknown difficulty {} (expected easy, normal, hard or insane)",
+                s
+            )),
+        }
+    }
+}
+
+/// A CPU **Controller** avoiding the missiles. When deciding, it predicts
+/// the trajectories of the missiles for the next ticks and looks for a
+/// sequence of actions keeping its **Spaceship** out of their way, using
+/// the same collision rules as the game. The first action of the sequence
+/// is played: advance when it is safe, otherwise wait or retreat.
+///
+/// Its **Difficulty** sets how far it looks ahead, how often it decides
+/// and how often it makes a mistake. The mistakes are drawn from its own
+/// seeded generator, so a game stays reproducible.
+pub struct DodgingController {
+    pub difficulty: Difficulty,
+    rng: Pcg64,
+    last_action: SpaceshipAction,
+    ticks_before_decision: u32,
+}
+
+impl DodgingController {
+    pub fn new(difficulty: Difficulty, seed: u64) -> DodgingController {
+        DodgingController {
+            difficulty,
+            rng: Pcg64::seed_from_u64(seed),
+            last_action: SpaceshipAction::Idle,
+            ticks_before_decision: 0,
+        }
+    }
+
+    /// Find the preferred action keeping the **Spaceship** safe for the
+    /// next ticks, or advance if none does. The action is kept during the
+    /// reaction delay, so it must be safe for all these ticks.
+    fn decide(&self, gs: &GameState, spaceship: &Spaceship) -> SpaceshipAction {
+        // The spaceship is tested against the missiles once they moved: the
+        // action played now is checked against the missiles in two ticks.
+        let trajectories =
+            predict_missiles(&gs.missiles, self.difficulty.lookahead() + 1, &gs.config);
+        let mut unsafe_paths = HashSet::new();
+
+        let held_ticks = self.difficulty.reaction_delay() as usize + 1;
+
+        PREFERRED_ACTIONS
+            .into_iter()
+            .find(|action| {
+                is_safe_hold(
+                    &gs.config,
+                    spaceship,
+                    *action,
+                    held_ticks,
+                    &trajectories[1..],
+                    &mut unsafe_paths,
+                )
+            })
+            .unwrap_or(SpaceshipAction::Upward)
+    }
+}
+
+impl Controller for DodgingController {
+    fn next_action(&mut self, gs: &GameState, player: Player, _keys: &HeldKeys) -> SpaceshipAction {
+        let spaceship = gs.spaceship(player);
+        if !spaceship.is_alive {
+            self.ticks_before_decision = 0;
+            return SpaceshipAction::Idle;
+        }
+
+        if self.ticks_before_decision > 0 {
+            self.ticks_before_decision -= 1;
+            return self.last_action;
+        }
+        self.ticks_before_decision = self.difficulty.reaction_delay();
+
+        self.last_action = if self.rng.gen_bool(self.difficulty.mistake_rate()) {
+            PREFERRED_ACTIONS[self.rng.gen_range(0..PREFERRED_ACTIONS.len())]
+        } else {
+            self.decide(gs, spaceship)
+        };
+        self.last_action
+    }
+}
+
+/// Return the positions of the missiles for each of the next ticks, the
+/// first element being the positions after one tick, following the rules
+/// of the given configuration.
+pub fn predict_missiles(
+    missiles: &[Missile],
+    ticks: usize,
+    config: &GameConfig,
+) -> Vec<Vec<Missile>> {
+    let mut trajectories = Vec::with_capacity(ticks);
+    let mut current = missiles.to_vec();
+    for _ in 0..ticks {
+        for m in &mut current {
+            m.update(config);
+        }
+        trajectories.push(current.clone());
+    }
+    trajectories
+}
+
+/// Apply an action to a copy of the **Spaceship**, following the rules of
+/// **GameState::step**.
+fn apply_action(config: &GameConfig, spaceship: &Spaceship, action: SpaceshipAction) -> Spaceship {
+    let mut next = spaceship.clone();
+    next.steer(action, config.spaceship.speed, config.window.height);
+    next.update();
+    next
+}
+
+/// Check if playing the action during the given amount of ticks, then some
+/// other actions, keeps the **Spaceship** safe from the missiles.
+fn is_safe_hold(
+    config: &GameConfig,
+    spaceship: &Spaceship,
+    action: SpaceshipAction,
+    held_ticks: usize,
+    trajectories: &[Vec<Missile>],
+    unsafe_paths: &mut HashSet<(usize, u32)>,
+) -> bool {
+    if held_ticks <= 1 {
+        return is_safe_path(config, spaceship, action, trajectories, unsafe_paths);
+    }
+    let next = apply_action(config, spaceship, action);
+    if GameState::has_spaceship_scored(&next) {
+        return true;
+    }
+    let Some((missiles, remaining)) = trajectories.split_first() else {
+        return true;
+    };
+    !missiles.iter().any(|m| next.collide_with(m))
+        && is_safe_hold(config, &next, action, held_ticks - 1, remaining, unsafe_paths)
+}
+
+/// Check if playing the action then some other actions keeps the
+/// **Spaceship** safe from the missiles, whose future positions are given
+/// tick by tick. Reaching the top of the screen is always safe, as the
+/// **Spaceship** scores and respawns. The paths already known as unsafe
+/// (remaining ticks and vertical position) are not explored again.
+fn is_safe_path(
+    config: &GameConfig,
+    spaceship: &Spaceship,
+    action: SpaceshipAction,
+    trajectories: &[Vec<Missile>],
+    unsafe_paths: &mut HashSet<(usize, u32)>,
+) -> bool {
+    let next = apply_action(config, spaceship, action);
+    if GameState::has_spaceship_scored(&next) {
+        return true;
+    }
+
+    let Some((missiles, remaining)) = trajectories.split_first() else {
+        return true;
+    };
+    let path = (trajectories.len(), next.position.y.to_bits());
+    if unsafe_paths.contains(&path) {
+        return false;
+    }
+
+    let is_safe = !missiles.iter().any(|m| next.collide_with(m))
+        && PREFERRED_ACTIONS
+            .into_iter()
+            .any(|action| is_safe_path(config, &next, action, remaining, unsafe_paths));
+    if !is_safe {
+        unsafe_paths.insert(path);
+    }
+    is_safe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dodging_controller_beats_naive_one() {
+        let mut config = GameConfig::default();
+        config.game.duration = 20;
+        let keys = HeldKeys::new();
+
+        let (mut total_dodging, mut total_naive) = (0, 0);
+        for seed in 0..5 {
+            let mut gs = GameState::new(&config, seed);
+            let mut dodging = DodgingController::new(Difficulty::Normal, seed);
+            let mut naive = CpuController;
+            while !gs.is_game_elapsed() {
+                let inputs = TickInputs {
+                    p1: dodging.next_action(&gs, Player::One, &keys),
+                    p2: naive.next_action(&gs, Player::Two, &keys),
+                };
+                gs.step(inputs);
+            }
+            total_dodging += gs.score_p1;
+            total_naive += gs.score_p2;
+        }
+
+        assert!(
+            total_dodging > total_naive,
+            "dodging {} - naive {}",
+            total_dodging,
+            total_naive
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::*;
+
+/// The name of the asset directory next to the executable.
+pub const EXE_ASSET_DIR: &str = "asset";
+
+/// The name of the asset directory in the data directories
+/// (`$XDG_DATA_HOME` and `/usr/share`).
+pub const DATA_ASSET_DIR: &str = "space_race";
+
+/// The system-wide data directory, where the game's assets are
+/// installed by the Linux distributions.
+pub const SYSTEM_DATA_DIR: &str = "/usr/share";
+
+/// The files used by the front end (fonts and sounds).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Asset {
+    PoetsenFont,
+    SchluberFont,
+    CollisionSound,
+    ScoreSound,
+}
+
+impl Asset {
+    /// The path of the asset, relative to an asset directory.
+    pub fn path(&self) -> &'static Path {
+        Path::new(match self {
+            Asset::PoetsenFont => "font/poetsen_one/PoetsenOne-Regular.ttf",
+            Asset::SchluberFont => "font/schluber/Schluber.ttf",
+            Asset::CollisionSound => "sfx/pew.wav",
+            Asset::ScoreSound => "sfx/score.mp3",
+        })
+    }
+
+    /// The content of the asset compiled into the executable.
+    #[cfg(feature = "embed-assets")]
+    fn embedded(&self) -> Option<&'static [u8]> {
+        let content: &'static [u8] = match self {
+            Asset::PoetsenFont => {
+                include_bytes!("../asset/font/poetsen_one/PoetsenOne-Regular.ttf")
+            }
+            Asset::SchluberFont => include_bytes!("../asset/font/schluber/Schluber.ttf"),
+            Asset::CollisionSound => include_bytes!("../asset/sfx/pew.wav"),
+            Asset::ScoreSound => include_bytes!("../asset/sfx/score.mp3"),
+        };
+        Some(content)
+    }
+
+    /// The assets are not compiled into the executable without the
+    /// `embed-assets` feature.
+    #[cfg(not(feature = "embed-assets"))]
+    fn embedded(&self) -> Option<&'static [u8]> {
+        None
+    }
+}
+
+/// Where the content of an asset is read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssetSource {
+    /// The file of the asset, found in one of the searched directories.
+    File(PathBuf),
+    /// The copy of the asset compiled into the executable.
+    Embedded(&'static [u8]),
+}
+
+/// The **Assets** struct locates and loads the content of the assets.
+/// An asset is read from the first of the searched directories containing
+/// its file, otherwise the copy compiled into the executable is used.
+///
+/// The directory given by `--assets` (if any) is always searched, and
+/// replaces the embedded assets file by file. Without the `embed-assets`
+/// feature, the following directories are then searched in order: the
+/// `asset` directory next to the executable, `$XDG_DATA_HOME/space_race`
+/// (`~/.local/share/space_race` by default) and `/usr/share/space_race`.
+#[derive(Clone, Debug, Default)]
+pub struct Assets {
+    dirs: Vec<PathBuf>,
+}
+
+impl Assets {
+    /// Create a new **Assets** struct, searching the given directory
+    /// before the default ones.
+    pub fn new(override_dir: Option<PathBuf>) -> Assets {
+        let mut dirs: Vec<PathBuf> = override_dir.into_iter().collect();
+        if !cfg!(feature = "embed-assets") {
+            dirs.extend(default_dirs());
+        }
+        Assets { dirs }
+    }
+
+    /// The directories searched for the asset files, in order.
+    pub fn search_dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Locate the given asset: its file in the first searched directory
+    /// containing it, otherwise its copy compiled into the executable.
+    pub fn locate(&self, asset: Asset) -> GameResult<AssetSource> {
+        let found = self
+            .dirs
+            .iter()
+            .map(|dir| dir.join(asset.path()))
+            .find(|path| path.exists());
+        if let Some(path) = found {
+            return Ok(AssetSource::File(path));
+        }
+        asset
+            .embedded()
+            .map(AssetSource::Embedded)
+            .ok_or_else(|| GameError::AssetMissing {
+                path: asset.path().to_path_buf(),
+                tried: self.dirs.iter().map(|dir| dir.join(asset.path())).collect(),
+            })
+    }
+
+    /// Load the content of the given asset.
+    pub fn load(&self, asset: Asset) -> GameResult<Cow<'static, [u8]>> {
+        match self.locate(asset)? {
+            AssetSource::File(path) => {
+                fs::read(&path)
+                    .map(Cow::Owned)
+                    .map_err(|e| GameError::AssetRead {
+                        path,
+                        reason: e.to_string(),
+                    })
+            }
+            AssetSource::Embedded(content) => Ok(Cow::Borrowed(content)),
+        }
+    }
+}
+
+/// The directories where the assets are installed: next to the
+/// executable, then in the user's and the system's data directories.
+fn default_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(exe_dir) = env::current_exe().ok().as_deref().and_then(Path::parent) {
+        dirs.push(exe_dir.join(EXE_ASSET_DIR));
+    }
+    if let Some(data_home) = user_data_dir() {
+        dirs.push(data_home.join(DATA_ASSET_DIR));
+    }
+    dirs.push(Path::new(SYSTEM_DATA_DIR).join(DATA_ASSET_DIR));
+    dirs
+}
+
+/// The user's data directory: `$XDG_DATA_HOME`, or `~/.local/share` if
+/// it is not set (or not an absolute path, as required by the XDG Base
+/// Directory specification).
+fn user_data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+}
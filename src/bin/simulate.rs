@@ -0,0 +1,85 @@
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use clap::Parser;
+
+use space_race::*;
+
+use std::path::PathBuf;
+
+/// Headless entry point. Simulate matches between two CPU players as fast
+/// as possible, without any window or audio, and print their scores. The
+/// match `i` uses the seed `i`, so the results are reproducible. By default
+/// the missile-dodging CPU (player 1) plays against the naive CPU (player 2).
+/// The rules are read from the configuration file, like the game does.
+#[derive(Parser)]
+#[command(name = "simulate", version, about)]
+struct Cli {
+    /// The amount of matches to simulate.
+    #[arg(default_value_t = 100)]
+    matches: u64,
+
+    /// The difficulty of the CPU players: easy, normal, hard or insane.
+    #[arg(long, default_value = "normal")]
+    difficulty: Difficulty,
+
+    /// The controller of the left spaceship: cpu or naive.
+    #[arg(long, default_value = "cpu")]
+    p1: ControllerKind,
+
+    /// The controller of the right spaceship: cpu or naive.
+    #[arg(long, default_value = "naive")]
+    p2: ControllerKind,
+
+    /// The time for 1 game in seconds.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    duration: Option<u64>,
+
+    /// The amount of missiles in the game.
+    #[arg(long)]
+    missiles: Option<usize>,
+
+    /// The configuration file to load, `space_race.toml` in the working
+    /// directory (if any) otherwise.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+pub fn main() {
+    let cli = Cli::parse();
+    let matches = cli.matches;
+    let kind_p1 = cli.p1.with_difficulty(cli.difficulty);
+    let kind_p2 = cli.p2.with_difficulty(cli.difficulty);
+
+    let config = GameConfig::load_with_overrides(cli.config.as_deref(), cli.duration, cli.missiles)
+        .unwrap_or_else(|e| Cli::command().error(ErrorKind::ValueValidation, e).exit())
+        .for_mode(GameMode::from_controllers(kind_p1, kind_p2));
+
+    let mut total_p1 = 0;
+    let mut total_p2 = 0;
+
+    let keys = HeldKeys::new();
+
+    for seed in 0..matches {
+        let mut gs = GameState::new(&config, seed);
+        let mut p1 = kind_p1.build(seed);
+        let mut p2 = kind_p2.build(seed.wrapping_add(1));
+        while !gs.is_game_elapsed() {
+            let inputs = TickInputs {
+                p1: p1.next_action(&gs, Player::One, &keys),
+                p2: p2.next_action(&gs, Player::Two, &keys),
+            };
+            gs.step(inputs);
+        }
+        println!("seed {}: {} - {}", seed, gs.score_p1, gs.score_p2);
+        total_p1 += gs.score_p1;
+        total_p2 += gs.score_p2;
+    }
+
+    if matches > 0 {
+        println!(
+            "average: {:.2} - {:.2}",
+            total_p1 as f64 / matches as f64,
+            total_p2 as f64 / matches as f64
+        );
+    }
+}
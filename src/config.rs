@@ -0,0 +1,576 @@
+use sdl2::pixels::Color;
+
+use serde::Deserialize;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::missile::*;
+use crate::spaceship::*;
+use crate::*;
+
+/// The path of the configuration file loaded when the game starts,
+/// relative to the working directory.
+pub const DEFAULT_CONFIG_PATH: &str = "space_race.toml";
+
+/// The smallest window's width accepted by the configuration (in pixels).
+const MIN_WINDOW_WIDTH: u32 = 200;
+
+/// The smallest window's height accepted by the configuration (in pixels).
+/// The spaceships must fit in the window, with room for the missiles.
+const MIN_WINDOW_HEIGHT: u32 = 200;
+
+/// The largest window's width accepted by the configuration (in pixels).
+/// The shapes of the entities are drawn with 16-bit coordinates, which
+/// must not overflow, even out of the window.
+const MAX_WINDOW_WIDTH: u32 = 8192;
+
+/// The largest window's height accepted by the configuration (in pixels),
+/// for the same reason as **MAX_WINDOW_WIDTH**.
+const MAX_WINDOW_HEIGHT: u32 = 8192;
+
+/// The highest speed accepted by the configuration (in pixels per tick).
+/// A faster entity would cross the window without ever being seen.
+const MAX_SPEED: f32 = 100.0;
+
+/// The largest size of a **Missile** accepted by the configuration, as a
+/// factor of the default size, keeping its shapes within 16-bit coordinates.
+const MAX_MISSILE_SIZE: f32 = 20.0;
+
+/// The longest game accepted by the configuration (in seconds).
+const MAX_GAME_DURATION: u64 = 3600;
+
+/// The **GameConfig** struct contains all the tunable values of the game.
+/// It is loaded from a TOML file, where every missing value keeps its
+/// default, so the game can be tuned without being recompiled.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub window: WindowConfig,
+    pub game: RulesConfig,
+    pub missile: MissileConfig,
+    pub spaceship: SpaceshipConfig,
+}
+
+/// The `[window]` section of the configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    /// Width of the game in logical pixels, and initial width of the
+    /// window. The game is scaled to the size of the window.
+    pub width: u32,
+    /// Height of the game in logical pixels, and initial height of the
+    /// window.
+    pub height: u32,
+}
+
+/// The `[game]` section of the configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesConfig {
+    /// The time between two ticks of the simulation in milliseconds.
+    /// The game is drawn at the display's refresh rate whatever this value.
+    pub tick_duration: u32,
+    /// The time for 1 game in seconds.
+    pub duration: u64,
+}
+
+/// The `[missile]` section of the configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MissileConfig {
+    /// The amount of **Missile** entities in the game.
+    pub quantity: usize,
+    /// The speed of the missiles (in pixels per tick).
+    pub speed: f32,
+    /// The X axis range where a **Missile** can spawn, the whole
+    /// window's width if not set.
+    pub spawn_x: Option<[u32; 2]>,
+    /// The Y axis range where a **Missile** can spawn, from the top of
+    /// the window to the **Spaceship** area if not set.
+    pub spawn_y: Option<[u32; 2]>,
+    #[serde(with = "rgb")]
+    pub head_color: Color,
+    #[serde(with = "rgb")]
+    pub body_color: Color,
+    #[serde(with = "rgb")]
+    pub tail_color: Color,
+    /// The variety of the missiles, in every game mode.
+    pub variety: MissileVariety,
+    /// The variety of the missiles against the CPU, replacing the default
+    /// one if set.
+    pub versus_cpu: Option<MissileVariety>,
+    /// The variety of the missiles between two players, replacing the
+    /// default one if set.
+    pub two_players: Option<MissileVariety>,
+}
+
+/// The `[missile.variety]` section of the configuration, and the
+/// `[missile.versus_cpu]` and `[missile.two_players]` sections replacing
+/// it in a game mode. Every **Missile** gets a random speed, size and
+/// drift within these ranges. By default, all the missiles are alike.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MissileVariety {
+    /// The ranges of speed (in pixels per tick). A **Missile** picks one
+    /// of the bands, then a speed within it. The speed of the `[missile]`
+    /// section is used if there is none.
+    pub speed_bands: Vec<[f32; 2]>,
+    /// The range of size, as a factor of the default size.
+    pub size: [f32; 2],
+    /// The highest vertical speed (in pixels per tick), upward or
+    /// downward. The missiles only move horizontally if it is 0.
+    pub max_drift: f32,
+}
+
+/// The `[spaceship]` section of the configuration.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpaceshipConfig {
+    /// The speed of the spaceships (in pixels per tick).
+    pub speed: f32,
+    /// The time before a destroyed **Spaceship** respawns (in seconds).
+    pub death_time: u64,
+    #[serde(with = "rgb")]
+    pub body_color: Color,
+    #[serde(with = "rgb")]
+    pub porthole_color: Color,
+    #[serde(with = "rgb")]
+    pub head_color: Color,
+    #[serde(with = "rgb")]
+    pub tail_color: Color,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+        }
+    }
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        RulesConfig {
+            tick_duration: TICK_DURATION,
+            duration: GAME_DURATION.as_secs(),
+        }
+    }
+}
+
+impl Default for MissileConfig {
+    fn default() -> Self {
+        MissileConfig {
+            quantity: MISSILE_QUANTITY,
+            speed: MISSILE_SPEED,
+            spawn_x: None,
+            spawn_y: None,
+            head_color: MISSILE_HEAD_COLOR,
+            body_color: MISSILE_BODY_COLOR,
+            tail_color: MISSILE_TAIL_COLOR,
+            variety: MissileVariety::default(),
+            versus_cpu: None,
+            two_players: None,
+        }
+    }
+}
+
+impl Default for MissileVariety {
+    fn default() -> Self {
+        MissileVariety {
+            speed_bands: Vec::new(),
+            size: [1.0, 1.0],
+            max_drift: 0.0,
+        }
+    }
+}
+
+impl Default for SpaceshipConfig {
+    fn default() -> Self {
+        SpaceshipConfig {
+            speed: SPACESHIP_SPEED,
+            death_time: SPACESHIP_DEATH_TIME.as_secs(),
+            body_color: SPACESHIP_BODY_COLOR,
+            porthole_color: SPACESHIP_PORTHOLE_COLOR,
+            head_color: SPACESHIP_HEAD_COLOR,
+            tail_color: SPACESHIP_TAIL_COLOR,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Load and validate the configuration from the given TOML file.
+    pub fn load(path: &Path) -> Result<GameConfig, ConfigError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let config: GameConfig = toml::from_str(&content)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), Box::new(e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load the configuration from the given file if it exists, or
+    /// use the default configuration otherwise.
+    pub fn load_or_default(path: &Path) -> Result<GameConfig, ConfigError> {
+        if path.exists() {
+            GameConfig::load(path)
+        } else {
+            Ok(GameConfig::default())
+        }
+    }
+
+    /// Load the configuration from the given file, or from the default
+    /// file (**DEFAULT_CONFIG_PATH**) if none is given. Unlike a given
+    /// file, the default file may not exist.
+    pub fn load_from(path: Option<&Path>) -> Result<GameConfig, ConfigError> {
+        match path {
+            Some(path) => GameConfig::load(path),
+            None => GameConfig::load_or_default(Path::new(DEFAULT_CONFIG_PATH)),
+        }
+    }
+
+    /// Load the configuration like **GameConfig::load_from**, then apply
+    /// the game's duration and amount of missiles given on the command
+    /// line (if any), which are validated as well.
+    pub fn load_with_overrides(
+        path: Option<&Path>,
+        duration: Option<u64>,
+        missiles: Option<usize>,
+    ) -> Result<GameConfig, ConfigError> {
+        let mut config = GameConfig::load_from(path)?;
+        if let Some(duration) = duration {
+            config.game.duration = duration;
+        }
+        if let Some(missiles) = missiles {
+            config.missile.quantity = missiles;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the values can be used to play a game.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.window.width < MIN_WINDOW_WIDTH {
+            return Err(ConfigError::invalid(
+                "window.width",
+                format!("must be at least {}", MIN_WINDOW_WIDTH),
+            ));
+        }
+        if self.window.height < MIN_WINDOW_HEIGHT {
+            return Err(ConfigError::invalid(
+                "window.height",
+                format!("must be at least {}", MIN_WINDOW_HEIGHT),
+            ));
+        }
+        if self.window.width > MAX_WINDOW_WIDTH {
+            return Err(ConfigError::invalid(
+                "window.width",
+                format!("must be at most {}", MAX_WINDOW_WIDTH),
+            ));
+        }
+        if self.window.height > MAX_WINDOW_HEIGHT {
+            return Err(ConfigError::invalid(
+                "window.height",
+                format!("must be at most {}", MAX_WINDOW_HEIGHT),
+            ));
+        }
+        if self.game.tick_duration == 0 {
+            return Err(ConfigError::invalid("game.tick_duration", "must not be 0"));
+        }
+        if self.game.duration == 0 {
+            return Err(ConfigError::invalid("game.duration", "must not be 0"));
+        }
+        if self.game.duration > MAX_GAME_DURATION {
+            return Err(ConfigError::invalid(
+                "game.duration",
+                format!("must be at most {}", MAX_GAME_DURATION),
+            ));
+        }
+        validate_speed("missile.speed", self.missile.speed)?;
+        validate_speed("spaceship.speed", self.spaceship.speed)?;
+        validate_range("missile.spawn_x", self.missile.spawn_x, self.window.width)?;
+        validate_range("missile.spawn_y", self.missile.spawn_y, self.window.height)?;
+        validate_variety("missile.variety", &self.missile.variety)?;
+        if let Some(variety) = &self.missile.versus_cpu {
+            validate_variety("missile.versus_cpu", variety)?;
+        }
+        if let Some(variety) = &self.missile.two_players {
+            validate_variety("missile.two_players", variety)?;
+        }
+        Ok(())
+    }
+
+    /// The configuration of a game in the given mode, where the variety
+    /// of the missiles set for the mode (if any) replaces the default one.
+    pub fn for_mode(&self, mode: GameMode) -> GameConfig {
+        let variety = match mode {
+            GameMode::VersusCpu => &self.missile.versus_cpu,
+            GameMode::TwoPlayers => &self.missile.two_players,
+        };
+        let mut config = self.clone();
+        if let Some(variety) = variety {
+            config.missile.variety = variety.clone();
+        }
+        config
+    }
+
+    /// The time between two ticks of the simulation.
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_millis(self.game.tick_duration as u64)
+    }
+
+    /// The time for 1 game.
+    pub fn game_duration(&self) -> Duration {
+        Duration::from_secs(self.game.duration)
+    }
+
+    /// The time before a destroyed **Spaceship** respawns.
+    pub fn spaceship_death_time(&self) -> Duration {
+        Duration::from_secs(self.spaceship.death_time)
+    }
+
+    /// The X axis range where a **Missile** can spawn.
+    /// It's basically the whole screen's width.
+    pub fn missile_spawn_range_x(&self) -> RangeInclusive<u32> {
+        match self.missile.spawn_x {
+            Some([start, end]) => start..=end,
+            None => 0..=self.window.width,
+        }
+    }
+
+    /// The Y axis range where a **Missile** can spawn.
+    /// It's from the top of the screen to the **Spaceship** area,
+    /// in order to let them spawn without killing them instantly.
+    pub fn missile_spawn_range_y(&self) -> RangeInclusive<u32> {
+        match self.missile.spawn_y {
+            Some([start, end]) => start..=end,
+            None => 0..=(self.window.height - MISSILE_SPAWN_BOTTOM_MARGIN),
+        }
+    }
+}
+
+/// Check that an optional range is ordered and within the window.
+fn validate_range(name: &str, range: Option<[u32; 2]>, max: u32) -> Result<(), ConfigError> {
+    match range {
+        Some([start, end]) if start > end => Err(ConfigError::invalid(
+            name,
+            format!("the start ({}) is after the end ({})", start, end),
+        )),
+        Some([_, end]) if end > max => Err(ConfigError::invalid(
+            name,
+            format!("the end ({}) is outside of the window ({})", end, max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the ranges of a missile variety are ordered and positive.
+fn validate_variety(name: &str, variety: &MissileVariety) -> Result<(), ConfigError> {
+    let ranges = variety
+        .speed_bands
+        .iter()
+        .map(|band| (format!("{}.speed_bands", name), *band, MAX_SPEED))
+        .chain([(format!("{}.size", name), variety.size, MAX_MISSILE_SIZE)]);
+    for (field, [start, end], max) in ranges {
+        if !start.is_finite() || !end.is_finite() {
+            return Err(ConfigError::invalid(&field, "must be a finite number"));
+        }
+        if start <= 0.0 {
+            return Err(ConfigError::invalid(&field, "must be positive"));
+        }
+        if start > end {
+            return Err(ConfigError::invalid(
+                &field,
+                format!("the start ({}) is after the end ({})", start, end),
+            ));
+        }
+        if end > max {
+            return Err(ConfigError::invalid(
+                &field,
+                format!("must be at most {}", max),
+            ));
+        }
+    }
+    let field = format!("{}.max_drift", name);
+    if !variety.max_drift.is_finite() {
+        return Err(ConfigError::invalid(&field, "must be a finite number"));
+    }
+    if variety.max_drift < 0.0 {
+        return Err(ConfigError::invalid(&field, "must not be negative"));
+    }
+    if variety.max_drift > MAX_SPEED {
+        return Err(ConfigError::invalid(
+            &field,
+            format!("must be at most {}", MAX_SPEED),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a speed is positive and not above **MAX_SPEED**.
+fn validate_speed(field: &str, speed: f32) -> Result<(), ConfigError> {
+    if !speed.is_finite() {
+        return Err(ConfigError::invalid(field, "must be a finite number"));
+    }
+    if speed <= 0.0 {
+        return Err(ConfigError::invalid(field, "must be positive"));
+    }
+    if speed > MAX_SPEED {
+        return Err(ConfigError::invalid(
+            field,
+            format!("must be at most {}", MAX_SPEED),
+        ));
+    }
+    Ok(())
+}
+
+/// The errors which can happen while loading a configuration file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(PathBuf, io::Error),
+    /// The file is not a valid TOML configuration.
+    Parse(PathBuf, Box<toml::de::Error>),
+    /// A value can't be used to play a game.
+    Invalid { field: String, reason: String },
+}
+
+impl ConfigError {
+    fn invalid(field: &str, reason: impl Into<String>) -> ConfigError {
+        ConfigError::Invalid {
+            field: field.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => {
+                write!(f, "failed to read config file {}: {}", path.display(), e)
+            }
+            ConfigError::Parse(path, e) => {
+                write!(f, "failed to parse config file {}: {}", path.display(), e)
+            }
+            ConfigError::Invalid { field, reason } => {
+                write!(f, "invalid value for {} in config: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// (De)serialize a **Color** as an array of its red, green and blue values.
+mod rgb {
+    use sdl2::pixels::Color;
+    use serde::Deserialize;
+    use serde::Deserializer;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b] = <[u8; 3]>::deserialize(deserializer)?;
+        Ok(Color::RGB(r, g, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse and validate a configuration file's content.
+    fn parse(content: &str) -> Result<GameConfig, ConfigError> {
+        let config: GameConfig = toml::from_str(content)
+            .map_err(|e| ConfigError::Parse(PathBuf::from("test.toml"), Box::new(e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the content is rejected because of the given field.
+    fn assert_invalid(content: &str, expected: &str) {
+        match parse(content) {
+            Err(ConfigError::Invalid { field, .. }) => assert_eq!(field, expected),
+            other => panic!("expected {} to be invalid, got {:?}", expected, other),
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(parse("").unwrap(), GameConfig::default());
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_too_small_window() {
+        assert_invalid("[window]\nwidth = 199", "window.width");
+        assert_invalid("[window]\nheight = 199", "window.height");
+    }
+
+    #[test]
+    fn rejects_too_large_window() {
+        assert_invalid("[window]\nwidth = 8193", "window.width");
+        assert_invalid("[window]\nheight = 8193", "window.height");
+    }
+
+    #[test]
+    fn rejects_null_durations() {
+        assert_invalid("[game]\ntick_duration = 0", "game.tick_duration");
+        assert_invalid("[game]\nduration = 0", "game.duration");
+    }
+
+    #[test]
+    fn rejects_too_long_game() {
+        assert!(parse("[game]\nduration = 3600").is_ok());
+        assert_invalid("[game]\nduration = 3601", "game.duration");
+        assert_invalid("[game]\nduration = 4294967296", "game.duration");
+    }
+
+    #[test]
+    fn rejects_non_positive_speeds() {
+        assert_invalid("[missile]\nspeed = 0.0", "missile.speed");
+        assert_invalid("[spaceship]\nspeed = -1.0", "spaceship.speed");
+    }
+
+    #[test]
+    fn rejects_non_finite_or_too_high_speeds() {
+        assert_invalid("[missile]\nspeed = nan", "missile.speed");
+        assert_invalid("[spaceship]\nspeed = inf", "spaceship.speed");
+        assert_invalid("[missile]\nspeed = 101.0", "missile.speed");
+    }
+
+    #[test]
+    fn rejects_invalid_missile_variety() {
+        let field = "missile.variety.speed_bands";
+        assert_invalid("[missile.variety]\nspeed_bands = [[0.0, 1.0]]", field);
+        assert_invalid("[missile.variety]\nspeed_bands = [[2.0, 1.0]]", field);
+        assert_invalid("[missile.variety]\nspeed_bands = [[1.0, inf]]", field);
+        assert_invalid("[missile.variety]\nspeed_bands = [[1.0, 200.0]]", field);
+        let field = "missile.variety.size";
+        assert_invalid("[missile.variety]\nsize = [nan, 1.0]", field);
+        assert_invalid("[missile.variety]\nsize = [1.0, 21.0]", field);
+        let field = "missile.versus_cpu.max_drift";
+        assert_invalid("[missile.versus_cpu]\nmax_drift = -1.0", field);
+        assert_invalid("[missile.versus_cpu]\nmax_drift = inf", field);
+    }
+
+    #[test]
+    fn rejects_invalid_spawn_ranges() {
+        assert_invalid("[missile]\nspawn_x = [500, 100]", "missile.spawn_x");
+        assert_invalid("[missile]\nspawn_x = [0, 801]", "missile.spawn_x");
+        assert_invalid("[missile]\nspawn_y = [300, 200]", "missile.spawn_y");
+        assert_invalid("[missile]\nspawn_y = [0, 601]", "missile.spawn_y");
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let is_parse_error = |content| matches!(parse(content), Err(ConfigError::Parse(..)));
+        assert!(is_parse_error("[window]\ndepth = 3"));
+        assert!(is_parse_error("[sound]\nvolume = 3"));
+    }
+}
@@ -0,0 +1,313 @@
+use sdl2::keyboard::KeyboardState;
+use sdl2::keyboard::Keycode;
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::TryRecvError;
+
+use crate::ai::*;
+use crate::game_state::*;
+
+/// The **HeldKeys** struct is the keyboard's state seen by the controllers
+/// during a tick. It is sampled by the front end once per frame, before
+/// the ticks of the frame; headless games can leave it empty.
+#[derive(Clone, Debug, Default)]
+pub struct HeldKeys {
+    keys: HashSet<Keycode>,
+}
+
+impl HeldKeys {
+    pub fn new() -> HeldKeys {
+        HeldKeys::default()
+    }
+
+    /// Sample the keys currently held from SDL's keyboard state.
+    pub fn from_keyboard_state(state: &KeyboardState) -> HeldKeys {
+        let mut keys = HeldKeys::new();
+        for key in state.pressed_scancodes().filter_map(Keycode::from_scancode) {
+            keys.press(key);
+        }
+        keys
+    }
+
+    /// Register the key as held.
+    pub fn press(&mut self, key: Keycode) {
+        self.keys.insert(key);
+    }
+
+    /// Register the key as not held anymore.
+    pub fn release(&mut self, key: Keycode) {
+        self.keys.remove(&key);
+    }
+
+    /// Release all the keys.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    pub fn is_held(&self, key: Keycode) -> bool {
+        self.keys.contains(&key)
+    }
+}
+
+/// The controllers of the game modes are chosen by the front end, so the
+/// simulation doesn't depend on any controller.
+impl GameMode {
+    /// The default controllers of the two spaceships for this mode.
+    pub fn controllers(&self) -> (ControllerKind, ControllerKind) {
+        match self {
+            GameMode::VersusCpu => (
+                ControllerKind::Arrows,
+                ControllerKind::Cpu(Difficulty::default()),
+            ),
+            GameMode::TwoPlayers => (ControllerKind::Arrows, ControllerKind::Ws),
+        }
+    }
+
+    /// The mode matching the given controllers: a single human player
+    /// against the CPU, on either side, is **GameMode::VersusCpu**, any
+    /// other setup is considered as **GameMode::TwoPlayers**.
+    pub fn from_controllers(p1: ControllerKind, p2: ControllerKind) -> GameMode {
+        if p1.is_human() != p2.is_human() {
+            GameMode::VersusCpu
+        } else {
+            GameMode::TwoPlayers
+        }
+    }
+}
+
+/// A **Controller** decides the action of a **Spaceship** at every tick.
+/// Any controller can drive any of the two spaceships, so a new way of
+/// playing (AI versus AI, hot-seat, online...) is only a matter of
+/// choosing the controllers of the game.
+pub trait Controller {
+    /// Return the action of the player's **Spaceship** for the next tick,
+    /// according to the current state of the game and the held keys.
+    fn next_action(&mut self, gs: &GameState, player: Player, keys: &HeldKeys) -> SpaceshipAction;
+}
+
+/// A **Controller** for a human player, moving the **Spaceship** while
+/// its upward or downward key is held.
+pub struct KeyboardController {
+    pub up_key: Keycode,
+    pub down_key: Keycode,
+}
+
+impl KeyboardController {
+    pub fn new(up_key: Keycode, down_key: Keycode) -> KeyboardController {
+        KeyboardController { up_key, down_key }
+    }
+
+    /// A **KeyboardController** using the up and down arrow keys.
+    pub fn arrows() -> KeyboardController {
+        KeyboardController::new(Keycode::Up, Keycode::Down)
+    }
+
+    /// A **KeyboardController** using the W (upward) and S (downward) keys.
+    pub fn ws() -> KeyboardController {
+        KeyboardController::new(Keycode::W, Keycode::S)
+    }
+}
+
+impl Controller for KeyboardController {
+    fn next_action(
+        &mut self,
+        _gs: &GameState,
+        _player: Player,
+        keys: &HeldKeys,
+    ) -> SpaceshipAction {
+        if keys.is_held(self.up_key) {
+            SpaceshipAction::Upward
+        } else if keys.is_held(self.down_key) {
+            SpaceshipAction::Downward
+        } else {
+            SpaceshipAction::Idle
+        }
+    }
+}
+
+/// The original (naive) CPU **Controller**. Its behaviour is quite
+/// simple: always toward the top of the screen.
+pub struct CpuController;
+
+impl Controller for CpuController {
+    fn next_action(
+        &mut self,
+        _gs: &GameState,
+        _player: Player,
+        _keys: &HeldKeys,
+    ) -> SpaceshipAction {
+        SpaceshipAction::Upward
+    }
+}
+
+/// A **Controller** playing back recorded actions, one per tick.
+/// The **Spaceship** stays idle once all the actions are played.
+pub struct ReplayController {
+    actions: Vec<SpaceshipAction>,
+    next: usize,
+}
+
+impl ReplayController {
+    pub fn new(actions: Vec<SpaceshipAction>) -> ReplayController {
+        ReplayController { actions, next: 0 }
+    }
+}
+
+impl Controller for ReplayController {
+    fn next_action(
+        &mut self,
+        _gs: &GameState,
+        _player: Player,
+        _keys: &HeldKeys,
+    ) -> SpaceshipAction {
+        let action = self.actions.get(self.next).copied().unwrap_or_default();
+        self.next += 1;
+        action
+    }
+}
+
+/// A **Controller** wrapping another one and recording every action it
+/// takes, so the game can be played back later with a **ReplayController**.
+pub struct RecordingController {
+    inner: Box<dyn Controller>,
+    pub actions: Vec<SpaceshipAction>,
+}
+
+impl RecordingController {
+    pub fn new(inner: Box<dyn Controller>) -> RecordingController {
+        RecordingController {
+            inner,
+            actions: Vec::new(),
+        }
+    }
+}
+
+impl Controller for RecordingController {
+    fn next_action(&mut self, gs: &GameState, player: Player, keys: &HeldKeys) -> SpaceshipAction {
+        let action = self.inner.next_action(gs, player, keys);
+        self.actions.push(action);
+        action
+    }
+}
+
+/// A **Controller** for a remote player. The actions are received through
+/// a channel fed by the network code. The last received action is kept
+/// until a new one arrives, and the **Spaceship** stays idle once the
+/// peer is disconnected.
+pub struct RemoteController {
+    receiver: Receiver<SpaceshipAction>,
+    last_action: SpaceshipAction,
+}
+
+impl RemoteController {
+    pub fn new(receiver: Receiver<SpaceshipAction>) -> RemoteController {
+        RemoteController {
+            receiver,
+            last_action: SpaceshipAction::Idle,
+        }
+    }
+}
+
+impl Controller for RemoteController {
+    fn next_action(
+        &mut self,
+        _gs: &GameState,
+        _player: Player,
+        _keys: &HeldKeys,
+    ) -> SpaceshipAction {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(action) => self.last_action = action,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.last_action = SpaceshipAction::Idle;
+                    break;
+                }
+            }
+        }
+        self.last_action
+    }
+}
+
+/// The controllers which can be chosen when the game starts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControllerKind {
+    Arrows,
+    Ws,
+    Cpu(Difficulty),
+    NaiveCpu,
+}
+
+impl ControllerKind {
+    /// Create a new **Controller** of this kind. The seed is used by the
+    /// controllers making random decisions.
+    pub fn build(&self, seed: u64) -> Box<dyn Controller> {
+        match self {
+            ControllerKind::Arrows => Box::new(KeyboardController::arrows()),
+            ControllerKind::Ws => Box::new(KeyboardController::ws()),
+            ControllerKind::Cpu(difficulty) => Box::new(DodgingController::new(*difficulty, seed)),
+            ControllerKind::NaiveCpu => Box::new(CpuController),
+        }
+    }
+
+    /// The same kind of controller, with the given difficulty if it
+    /// is a CPU which has one.
+    pub fn with_difficulty(&self, difficulty: Difficulty) -> ControllerKind {
+        match self {
+            ControllerKind::Cpu(_) => ControllerKind::Cpu(difficulty),
+            kind => *kind,
+        }
+    }
+
+    /// The difficulty of the controller, if it is a CPU which has one.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        match self {
+            ControllerKind::Cpu(difficulty) => Some(*difficulty),
+            _ => None,
+        }
+    }
+
+    /// Whether the controller is used by a human player.
+    pub fn is_human(&self) -> bool {
+        matches!(self, ControllerKind::Arrows | ControllerKind::Ws)
+    }
+}
+
+impl FromStr for ControllerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "arrows" => Ok(ControllerKind::Arrows),
+            "ws" => Ok(ControllerKind::Ws),
+            "cpu" => Ok(ControllerKind::Cpu(Difficulty::default())),
+            "naive" => Ok(ControllerKind::NaiveCpu),
+            _ => Err(format!(
+                "unknown controller {} (expected arrows, ws, cpu or naive)",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_human_against_cpu_is_versus_cpu() {
+        let (human, cpu) = (ControllerKind::Arrows, ControllerKind::Cpu(Difficulty::Hard));
+        assert_eq!(GameMode::from_controllers(human, cpu), GameMode::VersusCpu);
+        assert_eq!(GameMode::from_controllers(cpu, human), GameMode::VersusCpu);
+        assert_eq!(
+            GameMode::from_controllers(human, ControllerKind::Ws),
+            GameMode::TwoPlayers
+        );
+        assert_eq!(
+            GameMode::from_controllers(cpu, ControllerKind::NaiveCpu),
+            GameMode::TwoPlayers
+        );
+    }
+}
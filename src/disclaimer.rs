@@ -1,20 +1,20 @@
+use sdl2::event::Event;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::Canvas;
-use sdl2::render::Texture;
-use sdl2::video::Window;
+use sdl2::render::BlendMode;
 
+use std::path::Path;
 use std::time::Duration;
 
-use crate::GameFont;
-use crate::WINDOW_HEIGHT;
-use crate::WINDOW_WIDTH;
+use crate::error::*;
+use crate::game_font::*;
+use crate::scene::*;
+use crate::settings::*;
 
-/// Title's width of the disclaimer's title (in pixels).
-const TITLE_WIDTH: u32 = 200;
+/// Font size of the disclaimer's title (in points).
+const TITLE_FONT_SIZE: u16 = 72;
 
-/// Title's height of the disclaimer's title (in pixels).
-const TITLE_HEIGHT: u32 = 100;
+/// Font size of the disclaimer's message (in points).
+const MESSAGE_FONT_SIZE: u16 = 24;
 
 /// Title's content.
 const TITLE_STR: &str = "DISCLAIMER";
@@ -36,133 +36,104 @@ Have fun!";
 /// Space between the screen and the message (in pixels).
 const MESSAGE_MARGIN: u32 = 20;
 
-/// The duration of fading when the text appears and disappears (in seconds).
-const FADE_DURATION: u64 = 1;
-
-/// The duration of the screen (in seconds).
-const SCREEN_DURATION: u64 = 8;
-
-/// Display a disclaimer screen on the provided window and with the
-/// given fonts. 
-pub fn show_disclaimer(gf: &GameFont, canvas: &mut Canvas<Window>) {
-    canvas.set_draw_color(Color::BLACK);
-    canvas.clear();
-
-    let texture_creator = canvas.texture_creator();
-
-    let big_font = gf.get_font(gf.poetsen_path, 128);
-    let small_font = gf.get_font(gf.poetsen_path, 24);
-
-    let surface_title = gf.surface_from_str(TITLE_STR, &big_font, Color::WHITE);
-
-    let surface_message = small_font
-        .render(MESSAGE_STR)
-        .blended_wrapped(Color::WHITE, WINDOW_WIDTH - 2 * MESSAGE_MARGIN)
-        .expect("Failed to create font surface for Disclaimer's message!");
-
-    let rect_title = Rect::new(
-        (WINDOW_WIDTH / 2 - TITLE_WIDTH / 2) as i32,
-        0,
-        TITLE_WIDTH,
-        TITLE_HEIGHT,
-    );
-
-    let rect_message = Rect::new(
-        MESSAGE_MARGIN as i32,
-        (TITLE_HEIGHT + MESSAGE_MARGIN) as i32,
-        WINDOW_WIDTH - MESSAGE_MARGIN,
-        WINDOW_HEIGHT - MESSAGE_MARGIN * 2 - TITLE_HEIGHT,
-    );
-
-    let mut texture_title = texture_creator
-        .create_texture_from_surface(&surface_title)
-        .expect("Failed to create texte for Disclaimer's message's title!");
-
-    let mut texture_message = texture_creator
-        .create_texture_from_surface(&surface_message)
-        .expect("Failed to create texte for Disclaimer's message!");
-
-    // fade from black
-    fade_message(
-        &mut texture_title,
-        &mut texture_message,
-        rect_title,
-        rect_message,
-        canvas,
-        FADE_DURATION,
-        false,
-    );
-
-    message_to_screen(
-        &texture_title,
-        &texture_message,
-        rect_title,
-        rect_message,
-        canvas,
-        SCREEN_DURATION,
-    );
-
-    // fade to black
-    fade_message(
-        &mut texture_title,
-        &mut texture_message,
-        rect_title,
-        rect_message,
-        canvas,
-        FADE_DURATION,
-        true,
-    );
+/// The duration of fading when the text appears and disappears.
+const FADE_DURATION: Duration = Duration::from_secs(1);
+
+/// The duration of the screen, between the two fadings.
+const SCREEN_DURATION: Duration = Duration::from_secs(8);
+
+/// The **DisclaimerScene** struct is the scene showing a disclaimer
+/// message on the first launch of the game. The message fades from black,
+/// stays on screen for a while, then fades to black and reveals the scene
+/// below. Any key skips it.
+pub struct DisclaimerScene {
+    elapsed: Duration,
+}
+
+impl DisclaimerScene {
+    pub fn new() -> DisclaimerScene {
+        DisclaimerScene {
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// The brightness of the message, according to the time elapsed
+    /// since the scene started.
+    fn brightness(&self) -> u8 {
+        let fade_out_start = FADE_DURATION + SCREEN_DURATION;
+        let ratio = if self.elapsed < FADE_DURATION {
+            self.elapsed.as_secs_f64() / FADE_DURATION.as_secs_f64()
+        } else if self.elapsed < fade_out_start {
+            1.0
+        } else {
+            1.0 - (self.elapsed - fade_out_start).as_secs_f64() / FADE_DURATION.as_secs_f64()
+        };
+        (ratio.clamp(0.0, 1.0) * 255.0) as u8
+    }
 }
 
-/// Used by show_disclaimer() to copy the disclaimer's title and
-/// messages to screen for a specified duration.
-fn message_to_screen(
-    title: &Texture,
-    message: &Texture,
-    rect_title: Rect,
-    rect_message: Rect,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    duration: u64,
-) {
-    canvas
-        .copy(title, None, rect_title)
-        .expect("Failed to copy Disclaimer's title texture to canvas!");
-    canvas
-        .copy(message, None, rect_message)
-        .expect("Failed to copy Disclaimer's message texture to canvas!");
-    canvas.present();
-    ::std::thread::sleep(Duration::new(duration, 0));
+impl Default for DisclaimerScene {
+    fn default() -> Self {
+        DisclaimerScene::new()
+    }
 }
 
-/// Used by show_disclaimer() to fade the message at the beginning and
-/// at the end of disclaimer's screen duration.
-fn fade_message(
-    title: &mut Texture,
-    message: &mut Texture,
-    rect_title: Rect,
-    rect_message: Rect,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    duration: u64,
-    crescendo: bool,
-) {
-    // fade to the black
-    for i in 0..250 {
-        canvas.set_draw_color(Color::BLACK);
-        canvas.clear();
-        if !crescendo {
-            title.set_color_mod(i, i, i);
-            message.set_color_mod(i, i, i);
+impl Scene for DisclaimerScene {
+    /// Remember in the saved settings that the disclaimer was shown, so
+    /// it isn't shown again on the next launch.
+    fn exit(&mut self, ctx: &mut SceneContext) {
+        ctx.settings.has_seen_disclaimer = true;
+        let saved = Settings::update_saved(Path::new(SETTINGS_PATH), |settings| {
+            settings.has_seen_disclaimer = true;
+        });
+        if let Err(e) = saved {
+            eprintln!("Failed to save the settings: {}", e);
+        }
+    }
+
+    fn handle_event(&mut self, _ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown { .. } | Event::MouseButtonDown { .. } => Transition::Pop(1),
+            _ => Transition::None,
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut SceneContext, elapsed: Duration) -> Transition {
+        self.elapsed += elapsed;
+        if self.elapsed >= FADE_DURATION + SCREEN_DURATION + FADE_DURATION {
+            Transition::Pop(1)
         } else {
-            title.set_color_mod(250 - i, 250 - i, 250 - i);
-            message.set_color_mod(250 - i, 250 - i, 250 - i);
+            Transition::None
         }
-        message_to_screen(
-            title,
-            message,
-            rect_title,
-            rect_message,
-            canvas,
-            duration / 250,
-        );
+    }
+
+    /// Display the disclaimer screen with the given fonts.
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let (gf, canvas, window) = (&ctx.gf, &mut ctx.canvas, &ctx.config.window);
+        canvas.set_draw_color(Color::BLACK);
+        canvas.clear();
+
+        let title_style = TextStyle::new(&gf.poetsen, TITLE_FONT_SIZE, Color::WHITE);
+        let title_layout = TextLayout::new((window.width / 2) as i32, 0, Anchor::Top);
+        let rect_title = gf.draw_text(canvas, TITLE_STR, &title_style, &title_layout)?;
+
+        let message_style = TextStyle::new(&gf.poetsen, MESSAGE_FONT_SIZE, Color::WHITE);
+        let message_layout = TextLayout::new(
+            MESSAGE_MARGIN as i32,
+            rect_title.bottom() + MESSAGE_MARGIN as i32,
+            Anchor::TopLeft,
+        )
+        .with_max_width(window.width - 2 * MESSAGE_MARGIN);
+        gf.draw_text(canvas, MESSAGE_STR, &message_style, &message_layout)?;
+
+        // Fade the text by darkening it, so its cached texture is kept.
+        let brightness = self.brightness();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 255 - brightness));
+        canvas
+            .fill_rect(None)
+            .map_err(|e| GameError::render("drawing failed for Disclaimer's fading", e))?;
+        canvas.set_blend_mode(BlendMode::None);
+        Ok(())
     }
 }
@@ -0,0 +1,63 @@
+use sdl2::render::Canvas;
+use sdl2::video::FullscreenType;
+use sdl2::video::Window;
+use sdl2::video::WindowPos;
+
+use crate::error::*;
+use crate::settings::*;
+
+/// The amount of displays (monitors) the game can be shown on.
+pub fn display_count(canvas: &Canvas<Window>) -> i32 {
+    canvas
+        .window()
+        .subsystem()
+        .num_video_displays()
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// The display the game is shown on: the given one, or the first one if
+/// it isn't connected.
+pub fn connected_display(canvas: &Canvas<Window>, display: u32) -> u32 {
+    if display < display_count(canvas) as u32 {
+        display
+    } else {
+        0
+    }
+}
+
+/// Show the window as set in the settings: centered on the selected
+/// display (the first one if it isn't connected), in fullscreen or not.
+pub fn apply_display_settings(canvas: &mut Canvas<Window>, settings: &Settings) -> GameResult<()> {
+    let display = connected_display(canvas, settings.display) as i32;
+    let window = canvas.window_mut();
+
+    // The window can only be moved to another display out of fullscreen.
+    window
+        .set_fullscreen(FullscreenType::Off)
+        .map_err(GameError::sdl)?;
+    let bounds = window
+        .subsystem()
+        .display_bounds(display)
+        .map_err(GameError::sdl)?;
+    let (width, height) = window.size();
+    window.set_position(
+        WindowPos::Positioned(bounds.x() + (bounds.width() as i32 - width as i32) / 2),
+        WindowPos::Positioned(bounds.y() + (bounds.height() as i32 - height as i32) / 2),
+    );
+
+    if settings.is_fullscreen {
+        window
+            .set_fullscreen(fullscreen_type(settings.fullscreen_mode))
+            .map_err(GameError::sdl)?;
+    }
+    Ok(())
+}
+
+/// The SDL fullscreen type showing the game in the given mode.
+fn fullscreen_type(mode: FullscreenMode) -> FullscreenType {
+    match mode {
+        FullscreenMode::Borderless => FullscreenType::Desktop,
+        FullscreenMode::Exclusive => FullscreenType::True,
+    }
+}
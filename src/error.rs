@@ -0,0 +1,78 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// The result of an operation of the SDL front end.
+pub type GameResult<T> = Result<T, GameError>;
+
+/// The **GameError** enum lists the failures of the SDL front end
+/// (window, assets, fonts, sounds and drawing) which prevent the game
+/// from running.
+#[derive(Debug)]
+pub enum GameError {
+    /// SDL or one of its subsystems (video, window, events...) could not
+    /// be initialized.
+    Sdl(String),
+    /// An asset file of the game was found in none of the searched paths.
+    AssetMissing { path: PathBuf, tried: Vec<PathBuf> },
+    /// An asset file exists but could not be read.
+    AssetRead { path: PathBuf, reason: String },
+    /// A font file exists but could not be loaded.
+    FontLoad { path: PathBuf, reason: String },
+    /// The audio device could not be opened.
+    AudioInit(String),
+    /// A sound file exists but could not be loaded.
+    AudioLoad { path: PathBuf, reason: String },
+    /// Drawing something to the window failed.
+    Render { context: String, reason: String },
+}
+
+impl GameError {
+    /// Create a **GameError::Render** from the failure of the given drawing.
+    #[cfg(feature = "frontend")]
+    pub(crate) fn render(context: &str, reason: impl fmt::Display) -> GameError {
+        GameError::Render {
+            context: context.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Create a **GameError::Sdl** from the failure of an SDL initialization.
+    pub fn sdl(reason: impl fmt::Display) -> GameError {
+        GameError::Sdl(reason.to_string())
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Sdl(reason) => write!(f, "failed to initialize SDL: {}", reason),
+            GameError::AssetMissing { path, tried } => {
+                write!(f, "missing asset file {}, tried:", path.display())?;
+                for path in tried {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            GameError::AssetRead { path, reason } => {
+                write!(
+                    f,
+                    "failed to read asset file {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            GameError::FontLoad { path, reason } => {
+                write!(f, "failed to load font {}: {}", path.display(), reason)
+            }
+            GameError::AudioInit(reason) => {
+                write!(f, "failed to initialize audio: {}", reason)
+            }
+            GameError::AudioLoad { path, reason } => {
+                write!(f, "failed to load sound {}: {}", path.display(), reason)
+            }
+            GameError::Render { context, reason } => write!(f, "{}: {}", context, reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+/// The **GameClock** struct measures the time of a game in ticks instead of
+/// reading the wall-clock. Every timed rule (game duration, respawn delay...)
+/// uses it, so a game can be paused, slowed down or stepped faster than real
+/// time, and always behaves the same way for the same amount of ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameClock {
+    ticks: u64,
+    tick_duration: Duration,
+}
+
+impl GameClock {
+    /// Create a new **GameClock** at tick 0, where every tick lasts
+    /// the given duration of game time.
+    pub fn new(tick_duration: Duration) -> GameClock {
+        GameClock {
+            ticks: 0,
+            tick_duration,
+        }
+    }
+
+    /// Advance the clock by one tick.
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// The amount of ticks since the clock was created.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The game time represented by a single tick.
+    pub fn tick_duration(&self) -> Duration {
+        self.tick_duration
+    }
+
+    /// The game time since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.tick_duration.as_nanos() as u64 * self.ticks)
+    }
+
+    /// The game time since the given game time, usually registered
+    /// earlier with **GameClock::elapsed**.
+    pub fn elapsed_since(&self, time: Duration) -> Duration {
+        self.elapsed().saturating_sub(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elapsed_counts_ticks() {
+        let mut clock = GameClock::new(Duration::from_millis(50));
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        for _ in 0..3 {
+            clock.tick();
+        }
+        assert_eq!(clock.ticks(), 3);
+        assert_eq!(clock.elapsed(), Duration::from_millis(150));
+    }
+
+    #[test]
+    fn elapsed_since_never_goes_negative() {
+        let mut clock = GameClock::new(Duration::from_millis(50));
+        clock.tick();
+        let time = clock.elapsed();
+        clock.tick();
+        clock.tick();
+        assert_eq!(clock.elapsed_since(time), Duration::from_millis(100));
+        assert_eq!(clock.elapsed_since(Duration::from_secs(1)), Duration::ZERO);
+    }
+}
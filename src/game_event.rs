@@ -1,81 +1,161 @@
-use crate::update_cpu;
+use crate::ai::Difficulty;
+use crate::controller::*;
+use crate::error::GameResult;
+use crate::game_over::GameOverScene;
+use crate::game_state::*;
+use crate::pause::PausedScene;
+use crate::scene::*;
 use crate::view::*;
-use crate::GameFont;
-use crate::GameSFX;
-use crate::GameState;
-use crate::spaceship::*;
 
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::EventPump;
 
-pub struct FrameEvent;
+use std::time::Duration;
 
-pub fn handle_game_events(
-    gs: &mut GameState,
-    event_pump: &mut EventPump,
-    sounds: &GameSFX,
-    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    gf: &GameFont,
-) {
-    let event = event_pump.wait_event();
+/// The maximum amount of ticks played in a single update. If the game
+/// is late by more ticks (the window was moved, the computer is too
+/// slow...), the remaining time is skipped instead of catching up.
+const MAX_TICKS_PER_UPDATE: u32 = 5;
 
-    if event.is_user_event() {
-        // update missile coordinates here
-        for m in &mut gs.missiles {
-            m.update();
-        }
+/// The **MatchOptions** struct contains the options given when the game
+/// starts and applied to all the matches: the controllers replacing the
+/// default ones of the chosen mode, and the seed of the games.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchOptions {
+    pub p1: Option<ControllerKind>,
+    pub p2: Option<ControllerKind>,
+    pub seed: Option<u64>,
+}
 
-        if gs.spaceship_p1.is_alive && gs.collision_occurred_for(&gs.spaceship_p1) {
-            gs.spaceship_p1.die();
-            sounds.play_collision();
-        }
-        if gs.spaceship_p2.is_alive && gs.collision_occurred_for(&gs.spaceship_p2) {
-            gs.spaceship_p2.die();
-            sounds.play_collision();
+impl MatchOptions {
+    /// The controllers of the two spaceships for the given mode, the CPU
+    /// playing at the given difficulty.
+    pub fn controllers(
+        &self,
+        mode: GameMode,
+        difficulty: Difficulty,
+    ) -> (ControllerKind, ControllerKind) {
+        let (default_p1, default_p2) = mode.controllers();
+        (
+            self.p1.unwrap_or(default_p1).with_difficulty(difficulty),
+            self.p2.unwrap_or(default_p2).with_difficulty(difficulty),
+        )
+    }
+}
+
+/// The **MatchControls** struct gathers the front end data of a match
+/// which is not part of the simulation: the controllers of both players
+/// and the keys held during the last tick.
+pub struct MatchControls {
+    pub p1: Box<dyn Controller>,
+    pub p2: Box<dyn Controller>,
+    pub keys: HeldKeys,
+}
+
+impl MatchControls {
+    /// Create the controls of a match. The controllers are seeded from
+    /// the game's seed, so a match stays reproducible.
+    pub fn new(p1: ControllerKind, p2: ControllerKind, seed: u64) -> MatchControls {
+        MatchControls {
+            p1: p1.build(seed),
+            p2: p2.build(seed.wrapping_add(1)),
+            keys: HeldKeys::new(),
         }
-        update_cpu(gs, sounds);
-        if !gs.spaceship_p1.is_alive && gs.spaceship_p1.can_respawn() {
-            gs.reset_spaceship_p1();
+    }
+}
+
+/// The **PlayingScene** struct is the scene of a match. The simulation
+/// advances at the fixed tick rate of the game's clock, whatever the frame
+/// rate: the time elapsed between frames is accumulated, and consumed by
+/// as many ticks as it lasts. At every tick, the simulation advances with
+/// the actions of the players' controllers, then the resulting events are
+/// played (sounds).
+///
+/// The held keys are sampled once per frame, before its ticks: SDL's
+/// keyboard state only changes when the events are polled, so all the
+/// ticks of a frame see the same keys. As a held key is read from this
+/// state rather than from the key repeat events, it moves a **Spaceship**
+/// at the same speed on every machine, whatever the key repeat rate.
+///
+/// Every frame, the entities are drawn between their positions at the
+/// previous tick and the current one, by the fraction of a tick left in
+/// the accumulated time.
+pub struct PlayingScene {
+    gs: GameState,
+    controls: MatchControls,
+    controllers: (ControllerKind, ControllerKind),
+    lag: Duration,
+    previous: Snapshot,
+}
+
+impl PlayingScene {
+    /// Create a new match between the given controllers, with the
+    /// configuration of their game mode. The game uses the seed of the
+    /// match options, or a new random one if none is given.
+    pub fn new(ctx: &SceneContext, controllers: (ControllerKind, ControllerKind)) -> PlayingScene {
+        let seed = ctx.match_options.seed.unwrap_or_else(rand::random);
+        let mode = GameMode::from_controllers(controllers.0, controllers.1);
+        let gs = GameState::new(&ctx.config.for_mode(mode), seed);
+        let (p1, p2) = controllers;
+        PlayingScene {
+            previous: Snapshot::capture(&gs),
+            controls: MatchControls::new(p1, p2, gs.seed),
+            gs,
+            controllers,
+            lag: Duration::ZERO,
         }
-        draw_game(canvas, gs, gf);
-    } else {
+    }
+}
+
+impl Scene for PlayingScene {
+    fn enter(&mut self, _ctx: &mut SceneContext) {
+        self.lag = Duration::ZERO;
+    }
+
+    fn handle_event(&mut self, _ctx: &mut SceneContext, event: &Event) -> Transition {
         match event {
-            Event::Quit { .. }
-            | Event::KeyDown {
-                keycode: Some(Keycode::Escape),
-                ..
-            } => {
-                gs.is_game_over = true;
-            }
-            Event::KeyDown {
-                keycode: Some(Keycode::Up),
-                ..
-            } => {
-                if gs.spaceship_p1.is_alive {
-                    gs.spaceship_p1.move_upward();
-                    if GameState::has_spaceship_scored(&gs.spaceship_p1) {
-                        sounds.play_score();
-                        gs.score_p1 += 1;
-                        gs.reset_spaceship_p1();
-                    }
-                }
-            }
-            Event::KeyDown {
-                keycode: Some(Keycode::Down),
-                ..
-            } => {
-                if gs.spaceship_p1.is_alive && gs.spaceship_p1.can_move_downward() {
-                    gs.spaceship_p1.move_downward();
-                }
-            }
             Event::KeyDown {
-                keycode: Some(Keycode::Space),
+                keycode: Some(Keycode::Escape | Keycode::P),
+                repeat: false,
                 ..
-            } => {
-                gs.is_game_restarted = true;
+            } => Transition::Push(Box::new(PausedScene::new(self.controllers))),
+            _ => Transition::None,
+        }
+    }
+
+    fn update(&mut self, ctx: &mut SceneContext, elapsed: Duration) -> Transition {
+        let tick_duration = self.gs.clock.tick_duration();
+        self.lag = (self.lag + elapsed).min(tick_duration * MAX_TICKS_PER_UPDATE);
+        if self.lag >= tick_duration {
+            self.controls.keys = HeldKeys::from_keyboard_state(&ctx.event_pump.keyboard_state());
+        }
+
+        while self.lag >= tick_duration {
+            self.lag -= tick_duration;
+            self.previous = Snapshot::capture(&self.gs);
+            let inputs = TickInputs {
+                p1: self
+                    .controls
+                    .p1
+                    .next_action(&self.gs, Player::One, &self.controls.keys),
+                p2: self
+                    .controls
+                    .p2
+                    .next_action(&self.gs, Player::Two, &self.controls.keys),
+            };
+            let events = self.gs.step(inputs);
+            ctx.sounds.play_events(&events);
+
+            if self.gs.is_game_elapsed() {
+                let game_over = GameOverScene::new(&self.gs, self.controllers);
+                return Transition::Replace(1, Box::new(game_over));
             }
-            _ => {}
         }
+        Transition::None
+    }
+
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let alpha = self.lag.as_secs_f32() / self.gs.clock.tick_duration().as_secs_f32();
+        draw_game(&mut ctx.canvas, &self.gs, &self.previous, alpha, &ctx.gf)
     }
 }
@@ -1,44 +1,413 @@
 use sdl2::pixels::Color;
-use sdl2::surface::Surface;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::render::Texture;
+use sdl2::render::TextureCreator;
+use sdl2::rwops::RWops;
 use sdl2::ttf::Font;
 use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::Window;
+use sdl2::video::WindowContext;
 
-use std::path::Path;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::OnceLock;
 
-/// The **GameFont** struct contains the paths to the fonts
-/// used in the game, especially for the disclaimer screen,
-/// the scores  and the game over screen.
-pub struct GameFont<'a> {
-    pub context: Sdl2TtfContext,
-    pub poetsen_path: &'a Path,
-    pub schluber_path: &'a Path,
+use crate::asset::*;
+use crate::error::*;
+
+/// The **FontData** struct locates a font file, embedded in the
+/// executable or on disk, opened at any size with **GameFont::get_font**.
+pub struct FontData {
+    pub asset: Asset,
+    pub source: AssetSource,
+}
+
+/// The font, size and color a text is drawn with.
+#[derive(Clone, Copy)]
+pub struct TextStyle<'a> {
+    pub font: &'a FontData,
+    pub point_size: u16,
+    pub color: Color,
+}
+
+impl TextStyle<'_> {
+    pub fn new(font: &FontData, point_size: u16, color: Color) -> TextStyle<'_> {
+        TextStyle {
+            font,
+            point_size,
+            color,
+        }
+    }
+}
+
+/// The horizontal alignment of the lines of a text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// The point of a text's bounding box placed at the position of its
+/// **TextLayout**.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// The offset of the anchor from the top left corner of a box of
+    /// the given size.
+    fn offset(&self, width: u32, height: u32) -> (i32, i32) {
+        let (w, h) = (width as i32, height as i32);
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (w / 2, 0),
+            Anchor::TopRight => (w, 0),
+            Anchor::Left => (0, h / 2),
+            Anchor::Center => (w / 2, h / 2),
+            Anchor::Right => (w, h / 2),
+            Anchor::BottomLeft => (0, h),
+            Anchor::Bottom => (w / 2, h),
+            Anchor::BottomRight => (w, h),
+        }
+    }
+
+    /// The alignment matching the horizontal side of the anchor.
+    fn align(&self) -> Align {
+        match self {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => Align::Left,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => Align::Center,
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => Align::Right,
+        }
+    }
+}
+
+/// Where and how a text is laid out: the anchor of its bounding box is
+/// placed at the given position, its lines are aligned inside the box,
+/// and they are wrapped to fit the maximum width (if any).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextLayout {
+    pub x: i32,
+    pub y: i32,
+    pub anchor: Anchor,
+    pub align: Align,
+    pub max_width: Option<u32>,
 }
 
-impl GameFont<'_> {
-    pub fn new() -> GameFont<'static> {
-        let ct = sdl2::ttf::init().unwrap_or_else(|_| panic!("Failed to initialize SDL_TTF!"));
+impl TextLayout {
+    /// Place the anchor of the text at the given position. The lines are
+    /// aligned on the side of the anchor, and never wrapped.
+    pub fn new(x: i32, y: i32, anchor: Anchor) -> TextLayout {
+        TextLayout {
+            x,
+            y,
+            anchor,
+            align: anchor.align(),
+            max_width: None,
+        }
+    }
+
+    /// Align the lines of the text with the given alignment instead.
+    pub fn with_align(self, align: Align) -> TextLayout {
+        TextLayout { align, ..self }
+    }
 
-        GameFont {
-            context: ct,
-            poetsen_path: Path::new("asset/font/poetsen_one/PoetsenOne-Regular.ttf"),
-            schluber_path: Path::new("asset/font/schluber/Schluber.ttf"),
+    /// Wrap the text between words to fit the given width (in pixels).
+    pub fn with_max_width(self, max_width: u32) -> TextLayout {
+        TextLayout {
+            max_width: Some(max_width),
+            ..self
         }
     }
+}
+
+/// What a text texture is rendered from, used to find it in the cache.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    text: String,
+    font: Asset,
+    point_size: u16,
+    color: Color,
+}
+
+/// A text texture of the cache, with the last frame it was drawn in.
+struct CachedTexture {
+    texture: Texture,
+    last_used: u64,
+}
 
-    pub fn get_font(&self, path: &Path, point_size: u16) -> Font {
-        self.context
-            .load_font(path, point_size)
-            .unwrap_or_else(|_| panic!("Failed to load font {}", path.display()))
+impl CachedTexture {
+    /// Free the texture, which is not freed when dropped.
+    fn destroy(self) {
+        // SAFETY: the texture creator of the **GameFont** owning the cache
+        // keeps the renderer of the texture alive.
+        unsafe { self.texture.destroy() }
     }
+}
+
+/// A font of the cache, opened at one size, with the last frame it was
+/// used in.
+struct CachedFont {
+    font: Rc<Font<'static, 'static>>,
+    last_used: u64,
+}
 
-    pub fn surface_from_str<'a>(
+/// The **GameFont** struct contains the fonts used in the game,
+/// especially for the disclaimer screen, the scores  and the
+/// game over screen.
+///
+/// The fonts opened and the texts rendered are cached, so drawing the
+/// same text again in the next frames costs nothing. A text texture is
+/// dropped at the end of the first frame it isn't drawn in, for example
+/// when a score changes, and so is a font, for example when the window
+/// is resized and the texts are rendered at another size.
+pub struct GameFont {
+    pub poetsen: FontData,
+    pub schluber: FontData,
+    texture_creator: TextureCreator<WindowContext>,
+    fonts: RefCell<HashMap<(Asset, u16), CachedFont>>,
+    textures: RefCell<HashMap<TextKey, CachedTexture>>,
+    frame: Cell<u64>,
+}
+
+impl GameFont {
+    /// Initialize SDL_TTF and locate the fonts, drawn with the given
+    /// canvas.
+    pub fn new(assets: &Assets, canvas: &Canvas<Window>) -> GameResult<GameFont> {
+        ttf_context()?;
+        let locate = |asset: Asset| -> GameResult<FontData> {
+            Ok(FontData {
+                asset,
+                source: assets.locate(asset)?,
+            })
+        };
+
+        Ok(GameFont {
+            poetsen: locate(Asset::PoetsenFont)?,
+            schluber: locate(Asset::SchluberFont)?,
+            texture_creator: canvas.texture_creator(),
+            fonts: RefCell::new(HashMap::new()),
+            textures: RefCell::new(HashMap::new()),
+            frame: Cell::new(0),
+        })
+    }
+
+    /// Open the given font at the given size, or reuse it if it was
+    /// already opened.
+    pub fn get_font(
+        &self,
+        font: &FontData,
+        point_size: u16,
+    ) -> GameResult<Rc<Font<'static, 'static>>> {
+        let frame = self.frame.get();
+        let key = (font.asset, point_size);
+        if let Some(cached) = self.fonts.borrow_mut().get_mut(&key) {
+            cached.last_used = frame;
+            return Ok(Rc::clone(&cached.font));
+        }
+
+        let context = ttf_context()?;
+        let opened = match &font.source {
+            AssetSource::File(path) => context.load_font(path, point_size),
+            AssetSource::Embedded(content) => RWops::from_bytes(content)
+                .and_then(|rwops| context.load_font_from_rwops(rwops, point_size)),
+        };
+        let opened = Rc::new(opened.map_err(|reason| GameError::FontLoad {
+            path: font.asset.path().to_path_buf(),
+            reason,
+        })?);
+        self.fonts.borrow_mut().insert(
+            key,
+            CachedFont {
+                font: Rc::clone(&opened),
+                last_used: frame,
+            },
+        );
+        Ok(opened)
+    }
+
+    /// Draw a text on the canvas at its natural size, so it is never
+    /// stretched, and return its bounding box. The text is split on
+    /// its line breaks, and between words to fit the layout's maximum
+    /// width.
+    ///
+    /// The sizes and positions are in logical pixels, but the text is
+    /// rendered at the size it is shown on screen, so it stays sharp
+    /// whatever the size of the window.
+    pub fn draw_text(
         &self,
+        canvas: &mut Canvas<Window>,
         text: &str,
-        font: &Font<'a, 'a>,
-        color: Color,
-    ) -> Surface<'a> {
-        font.render(text)
-            .blended(color)
-            .unwrap_or_else(|_| panic!("Failed to create surface from str {}", text))
+        style: &TextStyle,
+        layout: &TextLayout,
+    ) -> GameResult<Rect> {
+        let scale = render_scale(canvas);
+        let to_logical = |pixels: u32| (pixels as f32 / scale).round() as u32;
+        let point_size = ((style.point_size as f32 * scale).round() as u16).max(1);
+        let font = self.get_font(style.font, point_size)?;
+        let measure = |line: &str| -> GameResult<u32> {
+            if line.is_empty() {
+                return Ok(0);
+            }
+            font.size_of(line)
+                .map(|(width, _)| to_logical(width))
+                .map_err(|e| GameError::render(&format!("failed to measure str {}", line), e))
+        };
+
+        let mut lines: Vec<(String, u32)> = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                let is_too_wide = match layout.max_width {
+                    Some(max_width) => measure(&candidate)? > max_width,
+                    None => false,
+                };
+                if is_too_wide && !line.is_empty() {
+                    let width = measure(&line)?;
+                    lines.push((line, width));
+                    line = word.to_string();
+                } else {
+                    line = candidate;
+                }
+            }
+            let width = measure(&line)?;
+            lines.push((line, width));
+        }
+
+        let line_height = to_logical(font.recommended_line_spacing().max(font.height()) as u32);
+        let width = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+        let height = line_height * lines.len() as u32;
+        let (offset_x, offset_y) = layout.anchor.offset(width, height);
+        let bounds = Rect::new(layout.x - offset_x, layout.y - offset_y, width, height);
+
+        for (i, (line, line_width)) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let x = match layout.align {
+                Align::Left => bounds.left(),
+                Align::Center => bounds.left() + (width - line_width) as i32 / 2,
+                Align::Right => bounds.right() - *line_width as i32,
+            };
+            let y = bounds.top() + (i as u32 * line_height) as i32;
+            let key = TextKey {
+                text: line.clone(),
+                font: style.font.asset,
+                point_size,
+                color: style.color,
+            };
+            self.with_texture(style.font, key, |texture| {
+                let query = texture.query();
+                canvas.copy(
+                    texture,
+                    None,
+                    Rect::new(x, y, to_logical(query.width), to_logical(query.height)),
+                )
+            })?
+            .map_err(|e| GameError::render(&format!("failed to copy str {} to canvas", line), e))?;
+        }
+        Ok(bounds)
+    }
+
+    /// Forget the fonts and the text textures which were not used during
+    /// the frame that just ended.
+    pub fn end_frame(&self) {
+        let frame = self.frame.get();
+        self.fonts
+            .borrow_mut()
+            .retain(|_, cached| cached.last_used == frame);
+        let mut textures = self.textures.borrow_mut();
+        let (used, unused): (HashMap<_, _>, HashMap<_, _>) = textures
+            .drain()
+            .partition(|(_, cached)| cached.last_used == frame);
+        *textures = used;
+        unused.into_values().for_each(CachedTexture::destroy);
+        self.frame.set(frame + 1);
+    }
+
+    /// Find the texture of the given text in the cache, or render it
+    /// with the given font, and draw it with the given function.
+    fn with_texture<R>(
+        &self,
+        font: &FontData,
+        key: TextKey,
+        draw: impl FnOnce(&Texture) -> R,
+    ) -> GameResult<R> {
+        let frame = self.frame.get();
+        let mut textures = self.textures.borrow_mut();
+        if let Some(cached) = textures.get_mut(&key) {
+            cached.last_used = frame;
+            return Ok(draw(&cached.texture));
+        }
+
+        let font = self.get_font(font, key.point_size)?;
+        let surface = font.render(&key.text).blended(key.color).map_err(|e| {
+            GameError::render(
+                &format!("failed to create surface from str {}", key.text),
+                e,
+            )
+        })?;
+        let texture = self
+            .texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| {
+                GameError::render(&format!("failed to create texture for {}", key.text), e)
+            })?;
+        let cached = textures.entry(key).or_insert(CachedTexture {
+            texture,
+            last_used: frame,
+        });
+        Ok(draw(&cached.texture))
+    }
+}
+
+impl Drop for GameFont {
+    fn drop(&mut self) {
+        self.textures
+            .get_mut()
+            .drain()
+            .for_each(|(_, cached)| cached.destroy());
+    }
+}
+
+/// The SDL_TTF context, initialized once for the whole program: SDL_TTF
+/// can't be initialized again while it is, and the fonts opened with the
+/// context must not outlive it.
+fn ttf_context() -> GameResult<&'static Sdl2TtfContext> {
+    static CONTEXT: OnceLock<Sdl2TtfContext> = OnceLock::new();
+    if let Some(context) = CONTEXT.get() {
+        return Ok(context);
+    }
+    let context = sdl2::ttf::init().map_err(GameError::sdl)?;
+    Ok(CONTEXT.get_or_init(|| context))
+}
+
+/// The ratio between the pixels of the window and the logical pixels
+/// the game is drawn in.
+fn render_scale(canvas: &Canvas<Window>) -> f32 {
+    let (logical_width, logical_height) = canvas.logical_size();
+    if logical_width == 0 || logical_height == 0 {
+        return 1.0;
     }
+    let (output_width, output_height) = canvas
+        .output_size()
+        .unwrap_or((logical_width, logical_height));
+    (output_width as f32 / logical_width as f32).min(output_height as f32 / logical_height as f32)
 }
@@ -1,127 +1,164 @@
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
-use sdl2::EventPump;
 
 use std::cmp::Ordering;
-use std::time::Instant;
+use std::path::Path;
+use std::time::Duration;
 
-use crate::GameFont;
+use crate::controller::ControllerKind;
+use crate::error::*;
+use crate::game_event::PlayingScene;
+use crate::game_font::*;
+use crate::high_scores::*;
+use crate::scene::*;
+use crate::Difficulty;
+use crate::GameMode;
 use crate::GameState;
-use crate::WINDOW_HEIGHT;
-use crate::WINDOW_WIDTH;
 
 const VICTORY_TITLE: &str = "VICTORY";
 const DEFEAT_TITLE: &str = "DEFEAT";
 const DRAW_TITLE: &str= "DRAW";
+const P1_WINS_TITLE: &str = "PLAYER 1 WINS";
+const P2_WINS_TITLE: &str = "PLAYER 2 WINS";
 
-const TITLE_WIDTH: u32 = 200;
-const TITLE_HEIGHT: u32 = 100;
-const REPLAY_WIDTH: u32 = 600;
-const REPLAY_HEIGHT: u32 = 30;
+const BIG_FONT_SIZE: u16 = 72;
+const SMALL_FONT_SIZE: u16 = 22;
 
 const SCREEN_PADDING: i32 = 20;
-const SCREEN_DURATION: u64 = 10;
-
-pub fn show_game_over(
-    gs: &mut GameState, 
-    gf: &GameFont, 
-    canvas: &mut Canvas<Window>,
-    ev: &mut EventPump) -> bool {
-    canvas.set_draw_color(Color::BLACK);
-    canvas.clear();
-    
-    let texture_creator = canvas.texture_creator();
+const SCREEN_DURATION: Duration = Duration::from_secs(10);
 
-    let big_font = gf.get_font(gf.poetsen_path, 128);
-    
-    let small_font = gf.get_font(gf.poetsen_path, 128);
+/// The **GameOverScene** struct is the scene showing the result of a
+/// finished match. The players can replay with the same controllers,
+/// otherwise the game goes back to the main menu after a while.
+pub struct GameOverScene {
+    /// The score of the human player against the CPU, of player 1 otherwise.
+    score: u32,
+    /// The score of the CPU against a human player, of player 2 otherwise.
+    opponent_score: u32,
+    mode: GameMode,
+    difficulty: Option<Difficulty>,
+    controllers: (ControllerKind, ControllerKind),
+    seed: u64,
+    elapsed: Duration,
+}
 
-    let title_str: &str = 
-        match gs.score_p1.cmp(&gs.score_p2) {
-            Ordering::Less => { DEFEAT_TITLE },
-            Ordering::Equal => { DRAW_TITLE },
-            Ordering::Greater => { VICTORY_TITLE },
-        };
-    
-    let message_str: &str = 
-        match gs.score_p1.cmp(&gs.score_p2) {
-            Ordering::Less => {"You lost! Are you going to stop on a defeat ?"},
-            Ordering::Equal => { "It's a tie! One single point would have been enough to win!"},
-            Ordering::Greater => { "You won! You have nothing to prove anymore!"},        
+impl GameOverScene {
+    /// Create the game over scene of the given finished match.
+    pub fn new(gs: &GameState, controllers: (ControllerKind, ControllerKind)) -> GameOverScene {
+        let (p1, p2) = controllers;
+        let mode = GameMode::from_controllers(p1, p2);
+        let (score, opponent_score) = match mode {
+            GameMode::VersusCpu if p2.is_human() => (gs.score_p2, gs.score_p1),
+            _ => (gs.score_p1, gs.score_p2),
         };
-    
-    let replay_str: &str = "Press [space] key to replay now, or the game will end!";
-    
-    let surface_title = gf.surface_from_str(title_str, &big_font, Color::WHITE);
-    
-    let surface_message = gf.surface_from_str(message_str, &small_font, Color::WHITE);
-    
-    let surface_replay = gf.surface_from_str(replay_str, &small_font, Color::WHITE);
-    
-    let rect_title = Rect::new(
-        (WINDOW_WIDTH / 2 - TITLE_WIDTH / 2) as i32,
-        0,
-        TITLE_WIDTH,
-        TITLE_HEIGHT,
-    );
-    
-    let rect_message = Rect::new(
-        SCREEN_PADDING,
-        (WINDOW_HEIGHT / 2) as i32,
-        WINDOW_WIDTH - 2 * SCREEN_PADDING as u32,
-        30
-    );
-    
-    let rect_replay = Rect::new(
-        (WINDOW_WIDTH / 2 - REPLAY_WIDTH / 2) as i32,
-        (WINDOW_HEIGHT / 2) as i32 + SCREEN_PADDING + 30,
-        REPLAY_WIDTH,
-        REPLAY_HEIGHT
-    ); 
-    
-    let texture_title = texture_creator
-        .create_texture_from_surface(&surface_title)
-        .expect("Failed to create texture for Game Over's screen title!");
-    
-    let texture_message = texture_creator
-        .create_texture_from_surface(&surface_message)
-        .expect("Failed to create texture for Game Over's main message!");
-    
-    let texture_replay = texture_creator
-        .create_texture_from_surface(&surface_replay)
-        .expect("Failed to write replay's line in Game Over's screen!");
-    
-    canvas
-        .copy(&texture_title, None, rect_title)
-        .expect("Failed to copy Game Over's Title's texture to canvas!");
-    canvas
-        .copy(&texture_message, None, rect_message)
-        .expect("Failed to copy Game Over's message's texture to canvas!");
-    canvas
-        .copy(&texture_replay, None, rect_replay)
-        .expect("Failed to copy Game Over's replay's texture to canvas!");
-    
-    canvas.present();
-    
-    handle_game_over_events(ev)
+        GameOverScene {
+            score,
+            opponent_score,
+            mode,
+            difficulty: p2.difficulty().or(p1.difficulty()),
+            controllers,
+            seed: gs.seed,
+            elapsed: Duration::ZERO,
+        }
+    }
 }
 
-
-fn handle_game_over_events(ev: &mut EventPump) -> bool {
-    
-    let start = Instant::now();
-    while start.elapsed().as_secs() < SCREEN_DURATION {
-        let event = ev.wait_event();
-            match event {
-                Event::Quit {..} |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => { return false; },
-                Event::KeyDown { keycode: Some(Keycode::Space), .. } => { return true; },
-                _ => {}, 
+impl Scene for GameOverScene {
+    /// Keep the result among the high scores if it was a game against the CPU.
+    fn enter(&mut self, ctx: &mut SceneContext) {
+        let (GameMode::VersusCpu, Some(difficulty)) = (self.mode, self.difficulty) else {
+            return;
+        };
+        let high_score = HighScore {
+            score: self.score,
+            opponent_score: self.opponent_score,
+            difficulty,
+        };
+        if ctx.high_scores.record(high_score).is_some() {
+            if let Err(e) = ctx.high_scores.save(Path::new(HIGH_SCORES_PATH)) {
+                eprintln!("Failed to save the high scores: {}", e);
             }
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => { Transition::Pop(1) },
+            Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
+                Transition::Replace(1, Box::new(PlayingScene::new(ctx, self.controllers)))
+            },
+            _ => { Transition::None },
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut SceneContext, elapsed: Duration) -> Transition {
+        self.elapsed += elapsed;
+        if self.elapsed >= SCREEN_DURATION {
+            Transition::Pop(1)
+        } else {
+            Transition::None
+        }
+    }
+
+    /// Show the result of the game. Against the CPU, the result is told from
+    /// the human player's perspective, otherwise the winning player is named. The
+    /// seed of the match is also shown, so it can be replayed with `--seed`,
+    /// and so is the difficulty of the CPU if there was one.
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let (mode, difficulty) = (self.mode, self.difficulty);
+        let (gf, canvas) = (&ctx.gf, &mut ctx.canvas);
+        canvas.set_draw_color(Color::BLACK);
+        canvas.clear();
+    
+        let title_str: &str = 
+            match (mode, self.score.cmp(&self.opponent_score)) {
+                (_, Ordering::Equal) => { DRAW_TITLE },
+                (GameMode::VersusCpu, Ordering::Less) => { DEFEAT_TITLE },
+                (GameMode::VersusCpu, Ordering::Greater) => { VICTORY_TITLE },
+                (GameMode::TwoPlayers, Ordering::Less) => { P2_WINS_TITLE },
+                (GameMode::TwoPlayers, Ordering::Greater) => { P1_WINS_TITLE },
+            };
+    
+        let message_str: &str = 
+            match (mode, self.score.cmp(&self.opponent_score)) {
+                (_, Ordering::Equal) => { "It's a tie! One single point would have been enough to win!"},
+                (GameMode::VersusCpu, Ordering::Less) => {"You lost! Are you going to stop on a defeat ?"},
+                (GameMode::VersusCpu, Ordering::Greater) => { "You won! You have nothing to prove anymore!"},
+                (GameMode::TwoPlayers, Ordering::Less) => { "Player 2 wins! Player 1 surely wants a rematch!"},
+                (GameMode::TwoPlayers, Ordering::Greater) => { "Player 1 wins! Player 2 surely wants a rematch!"},
+            };
+    
+        let replay_str: &str = "Press [space] key to replay now, or go back to the menu!";
+    
+        let window_width = ctx.config.window.width;
+        let window_height = ctx.config.window.height;
+        let center_x = (window_width / 2) as i32;
+
+        let title_style = TextStyle::new(&gf.poetsen, BIG_FONT_SIZE, Color::WHITE);
+        let title_layout = TextLayout::new(center_x, 0, Anchor::Top);
+        gf.draw_text(canvas, title_str, &title_style, &title_layout)?;
+
+        let small_style = TextStyle::new(&gf.poetsen, SMALL_FONT_SIZE, Color::WHITE);
+        let message_layout = TextLayout::new(center_x, (window_height / 2) as i32, Anchor::Top)
+            .with_max_width(window_width - 2 * SCREEN_PADDING as u32);
+        let rect_message = gf.draw_text(canvas, message_str, &small_style, &message_layout)?;
+
+        let replay_layout = TextLayout::new(center_x, rect_message.bottom() + SCREEN_PADDING, Anchor::Top)
+            .with_max_width(window_width - 2 * SCREEN_PADDING as u32);
+        gf.draw_text(canvas, replay_str, &small_style, &replay_layout)?;
+
+        let seed_str = format!("Seed: {}", self.seed);
+        let seed_style = TextStyle::new(&gf.poetsen, SMALL_FONT_SIZE, Color::GREY);
+        let seed_layout = TextLayout::new(center_x, window_height as i32 - SCREEN_PADDING, Anchor::Bottom);
+        let rect_seed = gf.draw_text(canvas, &seed_str, &seed_style, &seed_layout)?;
+
+        if let Some(difficulty) = difficulty {
+            let difficulty_str = format!("Difficulty: {}", difficulty);
+            let difficulty_style = TextStyle::new(&gf.poetsen, SMALL_FONT_SIZE, Color::GREY);
+            let difficulty_layout = TextLayout::new(center_x, rect_seed.top(), Anchor::Bottom);
+            gf.draw_text(canvas, &difficulty_str, &difficulty_style, &difficulty_layout)?;
+        }
+        Ok(())
     }
-    false
 }
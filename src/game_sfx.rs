@@ -1,55 +1,94 @@
 use soloud::*;
 
-use std::path::Path;
+use crate::asset::*;
+use crate::error::*;
+use crate::game_state::StepEvent;
 
 /// The **GameSFX** struct contains the data related to sounds
 /// used in the game, especially when a **Spaceship** scores or
-/// collides with a **Missile**.
+/// collides with a **Missile**. Without audio, the game is silent.
 pub struct GameSFX {
+    audio: Option<GameAudio>,
+    is_muted: bool,
+}
+
+/// The audio device and the sounds loaded in it.
+struct GameAudio {
     soloud: Soloud,
     collision_wav: Wav,
     score_wav: Wav,
 }
 
 impl GameSFX {
-    
-    /// Create a GameSFX struct with paths to sounds set by default.
-    pub fn new() -> GameSFX {
-        let sl = Soloud::default().expect("Failed to get Soloud object!");
+    /// Create a GameSFX struct with the sounds of the given assets.
+    pub fn new(assets: &Assets) -> GameResult<GameSFX> {
+        let sl = Soloud::default().map_err(|e| GameError::AudioInit(e.to_string()))?;
 
-        let mut sounds = GameSFX {
+        let mut audio = GameAudio {
             soloud: sl,
             collision_wav: audio::Wav::default(),
             score_wav: audio::Wav::default(),
         };
 
-        let sfx_collision_path: &Path = std::path::Path::new("asset/sfx/pew.wav");
-        let sfx_score_path: &Path = std::path::Path::new("asset/sfx/score.mp3");
-        sounds
-            .collision_wav
-            .load(sfx_collision_path)
-            .unwrap_or_else(|_| {
-                panic!(
-                    "failed to load sfx file {} for collision",
-                    sfx_collision_path.display()
-                )
-            });
-        sounds.score_wav.load(sfx_score_path).unwrap_or_else(|_| {
-            panic!(
-                "failed to load sfx file {} for score",
-                sfx_score_path.display()
-            )
-        });
-        sounds
+        load_wav(&mut audio.collision_wav, assets, Asset::CollisionSound)?;
+        load_wav(&mut audio.score_wav, assets, Asset::ScoreSound)?;
+        Ok(GameSFX {
+            audio: Some(audio),
+            is_muted: false,
+        })
+    }
+
+    /// Create a GameSFX struct which doesn't play any sound, used when
+    /// the audio could not be initialized.
+    pub fn silent() -> GameSFX {
+        GameSFX {
+            audio: None,
+            is_muted: false,
+        }
     }
-    
+
+    /// Mute or unmute all the sounds of the game.
+    pub fn set_muted(&mut self, is_muted: bool) {
+        self.is_muted = is_muted;
+    }
+
     /// Play the audio file set to be used when a collision occurs.
     pub fn play_collision(&self) {
-        self.soloud.play(&self.collision_wav);
+        if let Some(audio) = self.audible() {
+            audio.soloud.play(&audio.collision_wav);
+        }
     }
 
     /// Play the audio file set to be used when a player scores.
     pub fn play_score(&self) {
-        self.soloud.play(&self.score_wav);
+        if let Some(audio) = self.audible() {
+            audio.soloud.play(&audio.score_wav);
+        }
     }
+
+    /// Play the sounds matching the events returned by a simulation step.
+    pub fn play_events(&self, events: &[StepEvent]) {
+        for event in events {
+            match event {
+                StepEvent::Collision(_) => self.play_collision(),
+                StepEvent::Score(_) => self.play_score(),
+            }
+        }
+    }
+
+    /// The audio to play the sounds with, unless there is none or the
+    /// sounds are muted.
+    fn audible(&self) -> Option<&GameAudio> {
+        self.audio.as_ref().filter(|_| !self.is_muted)
+    }
+}
+
+/// Load a sound asset into the given **Wav**. The sound is decoded
+/// while loading, so its content is not kept.
+fn load_wav(wav: &mut Wav, assets: &Assets, asset: Asset) -> GameResult<()> {
+    let content = assets.load(asset)?;
+    wav.load_mem(&content).map_err(|e| GameError::AudioLoad {
+        path: asset.path().to_path_buf(),
+        reason: e.to_string(),
+    })
 }
@@ -1,62 +1,207 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
 use std::time::Duration;
-use std::time::Instant;
 
+use crate::config::GameConfig;
+use crate::game_clock::GameClock;
 use crate::missile::*;
 use crate::spaceship::*;
 
+/// Identify one of the two players (and thus **Spaceship**) of a game.
+/// **Player::One** is on the left side, **Player::Two** on the right side.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Player {
+    One,
+    Two,
+}
+
+/// The way a game is played: against the computer, or by two players
+/// sharing the same keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GameMode {
+    #[default]
+    VersusCpu,
+    TwoPlayers,
+}
+
+/// The action a **Spaceship** performs during one tick of the simulation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SpaceshipAction {
+    #[default]
+    Idle,
+    Upward,
+    Downward,
+}
+
+/// The actions of both players for a single tick of the simulation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TickInputs {
+    pub p1: SpaceshipAction,
+    pub p2: SpaceshipAction,
+}
+
+/// Something noticeable that happened during a tick. The events are
+/// returned by **GameState::step** so the front end (sounds, effects...)
+/// can react to them without being part of the simulation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepEvent {
+    Collision(Player),
+    Score(Player),
+}
+
 /// A **GameState** struct represents the state of the game
 /// at any point during the execution. This struct contains
 /// many attributes that handle the game entities like **Missile**
 /// and **Spaceships**. It also handle the scores, the game duration,
-/// and others.
+/// and others. The missiles are laid out from its **seed**, which is the
+/// only randomness of the simulation, so two games with the same seed and
+/// inputs have the same outcome.
+/// The rules (sizes, speeds, durations...) come from its **config**.
 pub struct GameState {
     pub missiles: Vec<Missile>,
     pub spaceship_p1: Spaceship,
     pub spaceship_p2: Spaceship,
-    pub is_game_over: bool,
-    pub is_game_restarted: bool,
     pub score_p1: u32,
     pub score_p2: u32,
-    pub starting_time: Instant,
+    pub clock: GameClock,
     pub game_duration: Duration,
+    pub seed: u64,
+    pub config: GameConfig,
 }
 
 impl GameState {
-    pub fn new(m_quantity: usize, g_duration: Duration) -> GameState {
+    pub fn new(config: &GameConfig, seed: u64) -> GameState {
+        let mut rng = Pcg64::seed_from_u64(seed);
         let mut random_missiles = Vec::new();
 
-        for _ in 0..m_quantity {
-            random_missiles.push(Missile::new());
+        for _ in 0..config.missile.quantity {
+            random_missiles.push(Missile::new(&mut rng, config));
         }
 
         GameState {
-            is_game_over: false,
-            is_game_restarted: true,
             missiles: random_missiles,
-            spaceship_p1: Spaceship::new(SPACESHIP_P1_X, SPACESHIP_P1_Y),
-            spaceship_p2: Spaceship::new(SPACESHIP_P2_X, SPACESHIP_P2_Y),
+            spaceship_p1: GameState::spawn_spaceship(config, Player::One),
+            spaceship_p2: GameState::spawn_spaceship(config, Player::Two),
             score_p1: 0,
             score_p2: 0,
-            starting_time: Instant::now(),
-            game_duration: g_duration,
+            clock: GameClock::new(config.tick_duration()),
+            game_duration: config.game_duration(),
+            seed,
+            config: config.clone(),
+        }
+    }
+
+    /// Create the **Spaceship** of the given player at its starting
+    /// position: at the bottom of the window, in the middle of the left
+    /// half for **Player::One** and of the right half for **Player::Two**.
+    pub fn spawn_spaceship(config: &GameConfig, player: Player) -> Spaceship {
+        let quarter = config.window.width / 4;
+        let pos_x = match player {
+            Player::One => quarter,
+            Player::Two => 3 * quarter,
+        } - SPACESHIP_BODY_WIDTH / 2;
+        let pos_y = config.window.height - SPACESHIP_BODY_HEIGHT;
+        Spaceship::new(pos_x as i32, pos_y as i32, &config.spaceship)
+    }
+
+    /// Advance the simulation by one tick of its **GameClock**: move the
+    /// missiles, kill the spaceships hit by a missile, then apply the
+    /// players' actions (or respawn the dead spaceships). No window, canvas
+    /// or audio is needed, the events that occurred are returned instead.
+    pub fn step(&mut self, inputs: TickInputs) -> Vec<StepEvent> {
+        let mut events = Vec::new();
+        self.clock.tick();
+
+        for m in &mut self.missiles {
+            m.update(&self.config);
+        }
+
+        for player in [Player::One, Player::Two] {
+            let spaceship = self.spaceship(player);
+            if spaceship.is_alive && self.collision_occurred_for(spaceship) {
+                let clock = self.clock;
+                self.spaceship_mut(player).die(&clock);
+                events.push(StepEvent::Collision(player));
+            }
+        }
+
+        self.update_spaceship(Player::One, inputs.p1, &mut events);
+        self.update_spaceship(Player::Two, inputs.p2, &mut events);
+
+        events
+    }
+
+    /// Apply an action to the **Spaceship** of the given player. A dead
+    /// **Spaceship** ignores the action and respawns once its delay is over.
+    fn update_spaceship(
+        &mut self,
+        player: Player,
+        action: SpaceshipAction,
+        events: &mut Vec<StepEvent>,
+    ) {
+        let clock = self.clock;
+        let death_time = self.config.spaceship_death_time();
+        let speed = self.config.spaceship.speed;
+        let screen_height = self.config.window.height;
+        let spaceship = self.spaceship_mut(player);
+        if !spaceship.is_alive {
+            if spaceship.can_respawn(&clock, death_time) {
+                self.reset_spaceship(player);
+            }
+            return;
+        }
+
+        spaceship.steer(action, speed, screen_height);
+        spaceship.update();
+
+        if GameState::has_spaceship_scored(spaceship) {
+            events.push(StepEvent::Score(player));
+            match player {
+                Player::One => self.score_p1 += 1,
+                Player::Two => self.score_p2 += 1,
+            }
+            self.reset_spaceship(player);
+        }
+    }
+
+    /// Return the **Spaceship** controlled by the given player.
+    pub fn spaceship(&self, player: Player) -> &Spaceship {
+        match player {
+            Player::One => &self.spaceship_p1,
+            Player::Two => &self.spaceship_p2,
+        }
+    }
+
+    /// Return the **Spaceship** controlled by the given player, mutably.
+    pub fn spaceship_mut(&mut self, player: Player) -> &mut Spaceship {
+        match player {
+            Player::One => &mut self.spaceship_p1,
+            Player::Two => &mut self.spaceship_p2,
         }
     }
 
     pub fn has_spaceship_scored(spaceship: &Spaceship) -> bool {
-        spaceship.body.rect.y() + spaceship.body.rect.height() as i32 + SPACESHIP_TAIL_SIZE as i32
-            <= 0
+        spaceship.position.y + (SPACESHIP_BODY_HEIGHT + SPACESHIP_TAIL_SIZE as u32) as f32 <= 0.0
     }
 
     pub fn reset_spaceship_p1(&mut self) {
-        self.spaceship_p1 = Spaceship::new(SPACESHIP_P1_X, SPACESHIP_P1_Y);
+        self.spaceship_p1 = GameState::spawn_spaceship(&self.config, Player::One);
     }
 
     pub fn reset_spaceship_p2(&mut self) {
-        self.spaceship_p2 = Spaceship::new(SPACESHIP_P2_X, SPACESHIP_P2_Y);
+        self.spaceship_p2 = GameState::spawn_spaceship(&self.config, Player::Two);
+    }
+
+    pub fn reset_spaceship(&mut self, player: Player) {
+        match player {
+            Player::One => self.reset_spaceship_p1(),
+            Player::Two => self.reset_spaceship_p2(),
+        }
     }
 
     pub fn is_game_elapsed(&self) -> bool {
-        self.starting_time.elapsed().as_secs() > self.game_duration.as_secs()
+        self.clock.elapsed() >= self.game_duration
     }
 
     pub fn collision_occurred_for(&self, spaceship: &Spaceship) -> bool {
@@ -68,3 +213,97 @@ impl GameState {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vector;
+
+    const SEED: u64 = 42;
+
+    /// The inputs of the given tick: the first player always advances,
+    /// the second one also waits and retreats.
+    fn inputs_at(tick: usize) -> TickInputs {
+        let actions = [
+            SpaceshipAction::Upward,
+            SpaceshipAction::Upward,
+            SpaceshipAction::Idle,
+            SpaceshipAction::Downward,
+        ];
+        TickInputs {
+            p1: SpaceshipAction::Upward,
+            p2: actions[(tick / 3) % actions.len()],
+        }
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_same_game() {
+        let config = GameConfig::default();
+        let mut gs_a = GameState::new(&config, SEED);
+        let mut gs_b = GameState::new(&config, SEED);
+
+        let mut events = Vec::new();
+        for tick in 0..2000 {
+            let events_a = gs_a.step(inputs_at(tick));
+            assert_eq!(events_a, gs_b.step(inputs_at(tick)));
+            events.extend(events_a);
+        }
+
+        assert!(events.iter().any(|e| matches!(e, StepEvent::Collision(_))));
+        assert!(events.iter().any(|e| matches!(e, StepEvent::Score(_))));
+        assert_eq!(gs_a.score_p1, gs_b.score_p1);
+        assert_eq!(gs_a.score_p2, gs_b.score_p2);
+        let positions = |gs: &GameState| gs.missiles.iter().map(|m| m.position).collect::<Vec<_>>();
+        assert_eq!(positions(&gs_a), positions(&gs_b));
+    }
+
+    #[test]
+    fn missile_hitting_spaceship_gives_collision() {
+        let mut gs = GameState::new(&GameConfig::default(), SEED);
+        let mut missile = gs.missiles[0].clone();
+        missile.position = gs.spaceship_p1.position;
+        missile.velocity = Vector::ZERO;
+        gs.missiles = vec![missile];
+
+        let events = gs.step(TickInputs::default());
+
+        assert_eq!(events, vec![StepEvent::Collision(Player::One)]);
+        assert!(!gs.spaceship_p1.is_alive);
+        assert!(gs.spaceship_p2.is_alive);
+    }
+
+    #[test]
+    fn reaching_top_gives_score() {
+        let config = GameConfig::default();
+        let mut gs = GameState::new(&config, SEED);
+        gs.missiles.clear();
+        let height = (SPACESHIP_BODY_HEIGHT + SPACESHIP_TAIL_SIZE as u32) as f32;
+        gs.spaceship_p2.position.y = config.spaceship.speed - height;
+
+        let events = gs.step(TickInputs {
+            p1: SpaceshipAction::Idle,
+            p2: SpaceshipAction::Upward,
+        });
+
+        assert_eq!(events, vec![StepEvent::Score(Player::Two)]);
+        assert_eq!((gs.score_p1, gs.score_p2), (0, 1));
+        let spawn = GameState::spawn_spaceship(&config, Player::Two);
+        assert_eq!(gs.spaceship_p2.position, spawn.position);
+    }
+
+    #[test]
+    fn game_elapses_once_duration_passed() {
+        let mut config = GameConfig::default();
+        config.game.tick_duration = 50;
+        config.game.duration = 1;
+        let mut gs = GameState::new(&config, SEED);
+
+        // A 1s game is over at the 20th tick.
+        for _ in 0..19 {
+            gs.step(TickInputs::default());
+        }
+        assert!(!gs.is_game_elapsed());
+        gs.step(TickInputs::default());
+        assert!(gs.is_game_elapsed());
+    }
+}
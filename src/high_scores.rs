@@ -0,0 +1,70 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::ai::Difficulty;
+
+/// The path of the file keeping the high scores, relative to the
+/// working directory.
+pub const HIGH_SCORES_PATH: &str = "high_scores.toml";
+
+/// The amount of high scores kept.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+/// A **HighScore** is the result of player 1 in a game against the CPU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HighScore {
+    /// The score of player 1.
+    pub score: u32,
+    /// The score of the CPU.
+    pub opponent_score: u32,
+    /// The difficulty of the CPU.
+    pub difficulty: Difficulty,
+}
+
+/// The **HighScores** struct contains the best results against the CPU,
+/// from the highest score to the lowest one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HighScores {
+    pub scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Load the high scores from the given TOML file. There are no high
+    /// scores yet if the file doesn't exist.
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        if !path.exists() {
+            return Ok(HighScores::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save the high scores to the given TOML file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)
+    }
+
+    /// Insert a result among the high scores, after the equal scores
+    /// already registered. Return its rank (starting at 0), or nothing
+    /// if the score is too low to be kept.
+    pub fn record(&mut self, high_score: HighScore) -> Option<usize> {
+        let rank = self
+            .scores
+            .iter()
+            .position(|s| s.score < high_score.score)
+            .unwrap_or(self.scores.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.scores.insert(rank, high_score);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
@@ -0,0 +1,128 @@
+//! Rules, entities and SDL front end of the **Space Race** game.
+//!
+//! The simulation (**GameState**, **Spaceship**, **Missile** and their
+//! movements and collisions) runs without any window, canvas or audio,
+//! for example to simulate matches faster than real time. It only uses
+//! SDL's geometry and color types, so it only links against the SDL2
+//! library. The front end used by the `space_race` executable (scenes,
+//! drawing, fonts and sounds) needs SDL2_gfx, SDL2_ttf and SoLoud, and is
+//! behind the default `frontend` feature.
+
+extern crate sdl2;
+
+pub use crate::ai::*;
+pub use crate::asset::*;
+pub use crate::config::*;
+pub use crate::controller::*;
+#[cfg(feature = "frontend")]
+pub use crate::display::*;
+pub use crate::error::*;
+pub use crate::game_clock::GameClock;
+#[cfg(feature = "frontend")]
+pub use crate::game_font::GameFont;
+#[cfg(feature = "frontend")]
+pub use crate::game_sfx::GameSFX;
+pub use crate::game_state::*;
+pub use crate::high_scores::*;
+pub use crate::missile::*;
+#[cfg(feature = "frontend")]
+pub use crate::scene::*;
+pub use crate::settings::*;
+pub use crate::spaceship::*;
+pub use crate::vector::Vector;
+
+use std::time::Duration;
+
+/// The CPU controllers avoiding the missiles.
+pub mod ai;
+
+/// Load the fonts and sounds, embedded or from disk.
+pub mod asset;
+
+/// Load the tunable values of the game from a configuration file.
+pub mod config;
+
+/// Decide the actions of the spaceships (keyboard, CPU, replay...).
+pub mod controller;
+
+/// Show the window in fullscreen or not, on the selected display.
+#[cfg(feature = "frontend")]
+pub mod display;
+
+/// Show a short diclaimer message before the game.
+#[cfg(feature = "frontend")]
+pub mod disclaimer;
+
+/// The failures of the SDL front end.
+pub mod error;
+
+/// Measure the time of a game in ticks.
+pub mod game_clock;
+
+/// Play a match: the scene handling the events and ticks of the game.
+#[cfg(feature = "frontend")]
+pub mod game_event;
+
+/// Handle the fonts used in the disclaimer, game and game over.
+#[cfg(feature = "frontend")]
+pub mod game_font;
+
+/// Show a game over screen.
+#[cfg(feature = "frontend")]
+pub mod game_over;
+
+/// Handle the sounds used in the game (scoring and collision).
+#[cfg(feature = "frontend")]
+pub mod game_sfx;
+
+/// Keep the best results against the CPU.
+pub mod high_scores;
+
+/// Show the main menu and its screens (settings and high scores).
+#[cfg(feature = "frontend")]
+pub mod menu;
+
+/// Keep track of the pause menu's selection.
+#[cfg(feature = "frontend")]
+pub mod pause;
+
+/// Manage the entities, scores and game related data.
+pub mod game_state;
+
+/// Drive the screens of the game with a stack of scenes.
+#[cfg(feature = "frontend")]
+pub mod scene;
+
+/// The preferences of the players.
+pub mod settings;
+
+/// The **Missile** entity.
+pub mod missile;
+
+/// The **Spaceship** entity.
+pub mod spaceship;
+
+/// The positions and velocities of the entities.
+pub mod vector;
+
+/// Handle all drawing functions to the window.
+#[cfg(feature = "frontend")]
+pub mod view;
+
+/// Default window's width in pixels.
+pub const WINDOW_WIDTH: u32 = 800;
+
+/// Default window's height in pixels.
+pub const WINDOW_HEIGHT: u32 = 600;
+
+/// Title of the window.
+pub const WINDOW_TITLE: &str = "Space Race by Cyprien Borée";
+
+/// The default time between two ticks of the simulation in milliseconds.
+pub const TICK_DURATION: u32 = 50;
+
+/// The default time for 1 game in seconds.
+pub const GAME_DURATION: Duration = Duration::new(45, 0);
+
+/// The default amount of **Missile** entities in the game.
+pub const MISSILE_QUANTITY: usize = 20;
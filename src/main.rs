@@ -1,128 +1,224 @@
-extern crate sdl2;
-
-use crate::disclaimer::*;
-use crate::game_event::*;
-use crate::game_font::GameFont;
-use crate::game_over::*;
-use crate::game_sfx::GameSFX;
-use crate::game_state::GameState;
-use crate::spaceship::*;
-
-use sdl2::render::Canvas;
-use sdl2::video::Window;
-
-use std::time::Duration;
-
-/// Show a short diclaimer message before the game.
-mod disclaimer;
-
-/// Handle the events that happen during the game.
-mod game_event;
-
-/// Handle the fonts used in the disclaimer, game and game over.
-mod game_font;
-
-/// Show a game over screen.
-mod game_over;
-
-/// Handle the sounds used in the game (scoring and collision).
-mod game_sfx;
-
-/// Manage the entities, scores and game related data.
-mod game_state;
-
-/// The **Missile** entity.
-mod missile;
-
-/// The **Spaceship** entity.
-mod spaceship;
-
-/// Handle all drawing functions to the window.
-mod view;
-
-/// Window's width in pixels.
-const WINDOW_WIDTH: u32 = 800;
-
-/// Window's height in pixels.
-const WINDOW_HEIGHT: u32 = 600;
-
-/// Title of the window.
-const WINDOW_TITLE: &str = "Space Race by Cyprien Borée";
-
-/// The time between two frames in milliseconds.
-const FRAME_DURATION: u32 = 50;
-
-/// The time for 1 game in seconds.
-const GAME_DURATION: Duration = Duration::new(45, 0);
-
-/// The amount of **Missile** entities in the game.
-const MISSILE_QUANTITY: usize = 20;
-
-/// Program's entry point. Initialize the window, its canvas
-/// and the fonts used by the game.
-pub fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
+use space_race::disclaimer::*;
+use space_race::game_event::*;
+use space_race::menu::*;
+use space_race::*;
+
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use clap::Parser;
+
+use sdl2::video::FullscreenType;
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+/// The command-line options of the game. Every option overriding the
+/// configuration file takes precedence over it.
+#[derive(Parser)]
+#[command(
+    version,
+    about = "A reproduction of the Space Race game (Atari, 1973)."
+)]
+struct Cli {
+    /// The amount of human players selected in the main menu: 1 against
+    /// the CPU, or 2 sharing the same keyboard.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    players: u8,
+
+    /// The difficulty of the CPU: easy, normal, hard or insane, the one
+    /// of the saved settings otherwise. It can also be changed in the
+    /// settings.
+    #[arg(long)]
+    difficulty: Option<Difficulty>,
+
+    /// The controller of the left spaceship: arrows, ws, cpu or naive.
+    #[arg(long)]
+    p1: Option<ControllerKind>,
+
+    /// The controller of the right spaceship: arrows, ws, cpu or naive.
+    #[arg(long)]
+    p2: Option<ControllerKind>,
+
+    /// The time for 1 game in seconds.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    duration: Option<u64>,
+
+    /// The amount of missiles in the game.
+    #[arg(long)]
+    missiles: Option<usize>,
+
+    /// The seed of the games. The same seed always gives the same
+    /// missiles layout, a random one is used for every game otherwise.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Play in fullscreen: borderless (the default) or exclusive, which
+    /// changes the resolution of the display. It can also be toggled
+    /// with F11 or Alt+Enter.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        default_missing_value = "borderless"
+    )]
+    fullscreen: Option<FullscreenMode>,
+
+    /// The display (monitor) to show the game on, starting at 0.
+    #[arg(long, value_name = "INDEX")]
+    display: Option<u32>,
+
+    /// Synchronize the frames with the refresh rate of the display
+    /// (true or false).
+    #[arg(long, value_name = "BOOL")]
+    vsync: Option<bool>,
+
+    /// Start the game without showing the disclaimer, which is otherwise
+    /// only shown on the first launch.
+    #[arg(long)]
+    skip_disclaimer: bool,
+
+    /// The configuration file to load, `space_race.toml` in the working
+    /// directory (if any) otherwise.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Play without any sound.
+    #[arg(long)]
+    mute: bool,
+
+    /// A directory of assets replacing the default ones, file by file
+    /// (for example `sfx/pew.wav` or `font/schluber/Schluber.ttf`). It
+    /// is searched before the installed assets.
+    #[arg(long, value_name = "DIR")]
+    assets: Option<PathBuf>,
+}
 
-    let window = video_subsystem
-        .window(WINDOW_TITLE, WINDOW_WIDTH, WINDOW_HEIGHT)
-        .position_centered()
-        .build()
-        .unwrap();
+impl Cli {
+    /// Load the configuration file and apply the options overriding it.
+    fn game_config(&self) -> GameConfig {
+        GameConfig::load_with_overrides(self.config.as_deref(), self.duration, self.missiles)
+            .unwrap_or_else(|e| Cli::command().error(ErrorKind::ValueValidation, e).exit())
+    }
 
-    let mut canvas = window.into_canvas().build().unwrap();
+    /// Load the saved settings and apply the options overriding them.
+    fn settings(&self) -> Settings {
+        let mut settings = Settings::load(Path::new(SETTINGS_PATH)).unwrap_or_else(|e| {
+            eprintln!("Failed to load the settings: {}", e);
+            Settings::default()
+        });
+        if let Some(difficulty) = self.difficulty {
+            settings.difficulty = difficulty;
+        }
+        settings.is_muted |= self.mute;
+        if let Some(mode) = self.fullscreen {
+            settings.is_fullscreen = true;
+            settings.fullscreen_mode = mode;
+        }
+        if let Some(display) = self.display {
+            settings.display = display;
+        }
+        if let Some(vsync) = self.vsync {
+            settings.vsync = vsync;
+        }
+        settings
+    }
 
-    let gf = GameFont::new();
-    show_disclaimer(&gf, &mut canvas);
-    run_game(&sdl_context, &mut canvas, &gf);
+    /// The option of the main menu selected when the game starts.
+    fn menu_option(&self) -> MenuOption {
+        match self.players {
+            1 => MenuOption::PlayVsCpu,
+            _ => MenuOption::TwoPlayers,
+        }
+    }
 }
 
-/// Initialize the game main components and run the game loop.
-fn run_game(context: &sdl2::Sdl, canvas: &mut Canvas<Window>, gf: &GameFont) {
-    let mut gs: GameState = GameState::new(MISSILE_QUANTITY, GAME_DURATION);
-    let sounds: GameSFX = GameSFX::new();
-    let mut event_pump = context.event_pump().unwrap();
-    let ev = context.event().unwrap();
-    ev.register_custom_event::<FrameEvent>().unwrap();
-
-    let timer_subsystem = context.timer().unwrap();
-    let _timer = timer_subsystem.add_timer(
-        FRAME_DURATION,
-        Box::new(|| {
-            ev.push_custom_event(FrameEvent).unwrap();
-            FRAME_DURATION
-        }),
-    );
-
-    while gs.is_game_restarted {
-        gs = GameState::new(MISSILE_QUANTITY, GAME_DURATION);
-        gs.is_game_restarted = false;
-        while !gs.is_game_over && !gs.is_game_elapsed() && !gs.is_game_restarted {
-            handle_game_events(&mut gs, &mut event_pump, &sounds, canvas, gf);
-        }
-        if gs.is_game_elapsed() {
-            gs.is_game_restarted = show_game_over(&mut gs, gf, canvas, &mut event_pump);
-        }
+/// Program's entry point. Read the command-line options and run the
+/// game. A failure of the front end is printed before exiting with a
+/// non-zero code.
+pub fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(&cli) {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
 }
 
-/// Update the position for the Spaceship controled by
-/// the CPU. Its behaviour is quite simple: always toward
-/// the top of the screen.
-fn update_cpu(gs: &mut GameState, sounds: &GameSFX) {
-    if !gs.spaceship_p2.is_alive {
-        if gs.spaceship_p2.can_respawn() {
-            gs.reset_spaceship_p2();
-            return;
-        } else {
-            // The delay to respawn is not over.
-            return;
+/// Read the configuration and the settings, then initialize the window
+/// (shown as set in the settings), its canvas, the fonts and the sounds
+/// used by the game. The game is played without any sound if the audio
+/// can't be initialized. After the disclaimer, the main menu is shown
+/// until the players quit.
+fn run(cli: &Cli) -> GameResult<()> {
+    let config = cli.game_config();
+    let mut settings = cli.settings();
+
+    let sdl_context = sdl2::init().map_err(GameError::sdl)?;
+    let video_subsystem = sdl_context.video().map_err(GameError::sdl)?;
+
+    let mut window_builder =
+        video_subsystem.window(WINDOW_TITLE, config.window.width, config.window.height);
+    window_builder
+        .position_centered()
+        .resizable()
+        .allow_highdpi();
+    let window = window_builder.build().map_err(GameError::sdl)?;
+
+    // The game is drawn in a fixed logical resolution, scaled to the size
+    // of the window and letterboxed to keep its aspect ratio.
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "linear");
+    let mut canvas_builder = window.into_canvas();
+    if settings.vsync {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+    let mut canvas = canvas_builder.build().map_err(GameError::sdl)?;
+    canvas
+        .set_logical_size(config.window.width, config.window.height)
+        .map_err(GameError::sdl)?;
+    settings.display = connected_display(&canvas, settings.display);
+    if let Err(e) = apply_display_settings(&mut canvas, &settings) {
+        eprintln!(
+            "Failed to apply the display settings: {}, the game is played in a window",
+            e
+        );
+        settings.is_fullscreen = false;
+        if let Err(e) = canvas.window_mut().set_fullscreen(FullscreenType::Off) {
+            eprintln!("Failed to leave fullscreen: {}", e);
         }
     }
-    gs.spaceship_p2.move_upward();
-    if GameState::has_spaceship_scored(&gs.spaceship_p2) {
-        sounds.play_score();
-        gs.score_p2 += 1;
-        gs.reset_spaceship_p2();
+    let event_pump = sdl_context.event_pump().map_err(GameError::sdl)?;
+
+    let assets = Assets::new(cli.assets.clone());
+    let mut sounds = GameSFX::new(&assets).unwrap_or_else(|e| {
+        eprintln!("{}, the game is played without sound", e);
+        GameSFX::silent()
+    });
+    sounds.set_muted(settings.is_muted);
+    let high_scores = HighScores::load(Path::new(HIGH_SCORES_PATH)).unwrap_or_else(|e| {
+        eprintln!("Failed to load the high scores: {}", e);
+        HighScores::default()
+    });
+
+    let gf = GameFont::new(&assets, &canvas)?;
+
+    let mut ctx = SceneContext {
+        canvas,
+        event_pump,
+        gf,
+        sounds,
+        config,
+        settings,
+        high_scores,
+        match_options: MatchOptions {
+            p1: cli.p1,
+            p2: cli.p2,
+            seed: cli.seed,
+        },
+    };
+
+    let mut scenes: Vec<Box<dyn Scene>> = vec![Box::new(MenuScene::new(cli.menu_option()))];
+    if !cli.skip_disclaimer && !settings.has_seen_disclaimer {
+        scenes.push(Box::new(DisclaimerScene::new()));
     }
+    SceneManager::new(scenes).run(&mut ctx)
 }
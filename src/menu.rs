@@ -0,0 +1,380 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+
+use std::path::Path;
+
+use crate::display::*;
+use crate::error::*;
+use crate::game_event::PlayingScene;
+use crate::game_font::*;
+use crate::game_state::GameMode;
+use crate::scene::*;
+use crate::settings::*;
+
+/// The font sizes of the menu screens' titles and options (in points).
+const TITLE_FONT_SIZE: u16 = 72;
+const OPTION_FONT_SIZE: u16 = 28;
+
+/// The height of an option in a menu (in pixels).
+const OPTION_HEIGHT: u32 = 40;
+
+/// The color of the selected option in a menu.
+const COLOR_SELECTED: Color = Color::YELLOW;
+
+const SCREEN_PADDING: i32 = 20;
+
+/// The options of the main menu, in the order they are displayed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuOption {
+    PlayVsCpu,
+    TwoPlayers,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+impl MenuOption {
+    /// The text displayed for the option in the main menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuOption::PlayVsCpu => "Play vs CPU",
+            MenuOption::TwoPlayers => "Two Players",
+            MenuOption::Settings => "Settings",
+            MenuOption::HighScores => "High Scores",
+            MenuOption::Quit => "Quit",
+        }
+    }
+
+    /// The mode of the game started by the option, if it starts one.
+    pub fn game_mode(&self) -> Option<GameMode> {
+        match self {
+            MenuOption::PlayVsCpu => Some(GameMode::VersusCpu),
+            MenuOption::TwoPlayers => Some(GameMode::TwoPlayers),
+            _ => None,
+        }
+    }
+}
+
+/// All the **MenuOption** values, in the order they are displayed.
+pub const MENU_OPTIONS: [MenuOption; 5] = [
+    MenuOption::PlayVsCpu,
+    MenuOption::TwoPlayers,
+    MenuOption::Settings,
+    MenuOption::HighScores,
+    MenuOption::Quit,
+];
+
+/// The **MainMenu** struct keeps track of the option currently
+/// selected in the main menu.
+pub struct MainMenu {
+    pub selected: usize,
+}
+
+impl MainMenu {
+    /// Create a new **MainMenu** with the first option (play vs CPU) selected.
+    pub fn new() -> MainMenu {
+        MainMenu { selected: 0 }
+    }
+
+    /// Select the option above the current one, wrapping to the last one.
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + MENU_OPTIONS.len() - 1) % MENU_OPTIONS.len();
+    }
+
+    /// Select the option below the current one, wrapping to the first one.
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % MENU_OPTIONS.len();
+    }
+
+    /// Select the given option.
+    pub fn select(&mut self, option: MenuOption) {
+        self.selected = MENU_OPTIONS.iter().position(|o| *o == option).unwrap_or(0);
+    }
+
+    /// The option currently selected.
+    pub fn selected_option(&self) -> MenuOption {
+        MENU_OPTIONS[self.selected]
+    }
+}
+
+impl Default for MainMenu {
+    fn default() -> Self {
+        MainMenu::new()
+    }
+}
+
+/// The options of the settings screen, in the order they are displayed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsOption {
+    Difficulty,
+    Sound,
+    Fullscreen,
+    Display,
+    VSync,
+    Back,
+}
+
+impl SettingsOption {
+    /// The text displayed for the option, with the current value of the setting.
+    pub fn label(&self, settings: &Settings) -> String {
+        match self {
+            SettingsOption::Difficulty => format!("Difficulty: {}", settings.difficulty),
+            SettingsOption::Sound if settings.is_muted => String::from("Sound: Off"),
+            SettingsOption::Sound => String::from("Sound: On"),
+            SettingsOption::Fullscreen if settings.is_fullscreen => {
+                format!("Fullscreen: {}", settings.fullscreen_mode)
+            }
+            SettingsOption::Fullscreen => String::from("Fullscreen: Off"),
+            SettingsOption::Display => {
+                format!("Display: {}", settings.display.saturating_add(1))
+            }
+            // The canvas is only synchronized with the display when created.
+            SettingsOption::VSync if settings.vsync => String::from("VSync: On (restart)"),
+            SettingsOption::VSync => String::from("VSync: Off (restart)"),
+            SettingsOption::Back => String::from("Back"),
+        }
+    }
+}
+
+/// All the **SettingsOption** values, in the order they are displayed.
+pub const SETTINGS_OPTIONS: [SettingsOption; 6] = [
+    SettingsOption::Difficulty,
+    SettingsOption::Sound,
+    SettingsOption::Fullscreen,
+    SettingsOption::Display,
+    SettingsOption::VSync,
+    SettingsOption::Back,
+];
+
+/// The **MenuScene** struct is the scene of the main menu. An option is
+/// chosen with the up and down arrow keys, then the enter key. The game
+/// comes back to this scene once a match or another screen is over.
+pub struct MenuScene {
+    menu: MainMenu,
+}
+
+impl MenuScene {
+    /// Create the main menu scene with the given option selected.
+    pub fn new(selected: MenuOption) -> MenuScene {
+        let mut menu = MainMenu::new();
+        menu.select(selected);
+        MenuScene { menu }
+    }
+}
+
+impl Scene for MenuScene {
+    fn handle_event(&mut self, ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => self.menu.select_previous(),
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => self.menu.select_next(),
+            Event::KeyDown {
+                keycode: Some(Keycode::Return | Keycode::KpEnter),
+                ..
+            } => {
+                let option = self.menu.selected_option();
+                if let Some(mode) = option.game_mode() {
+                    let controllers = ctx.match_options.controllers(mode, ctx.settings.difficulty);
+                    return Transition::Push(Box::new(PlayingScene::new(ctx, controllers)));
+                }
+                return match option {
+                    MenuOption::Settings => Transition::Push(Box::new(SettingsScene::new())),
+                    MenuOption::HighScores => Transition::Push(Box::new(HighScoresScene)),
+                    _ => Transition::Quit,
+                };
+            }
+            _ => {}
+        }
+        Transition::None
+    }
+
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let labels: Vec<String> = MENU_OPTIONS.iter().map(|o| o.label().to_string()).collect();
+        draw_menu(ctx, "SPACE RACE", &labels, Some(self.menu.selected))
+    }
+}
+
+/// The **SettingsScene** struct is the scene changing the settings. The
+/// selected setting is changed with the left and right arrow keys (or the
+/// enter key), and the escape key goes back to the main menu. The settings
+/// changed on the screen are saved when leaving it, while the values given
+/// by the command-line options are only used for the current launch.
+pub struct SettingsScene {
+    selected: usize,
+    initial: Settings,
+}
+
+impl SettingsScene {
+    pub fn new() -> SettingsScene {
+        SettingsScene {
+            selected: 0,
+            initial: Settings::default(),
+        }
+    }
+}
+
+impl Default for SettingsScene {
+    fn default() -> Self {
+        SettingsScene::new()
+    }
+}
+
+impl Scene for SettingsScene {
+    fn enter(&mut self, ctx: &mut SceneContext) {
+        self.initial = ctx.settings;
+    }
+
+    fn exit(&mut self, ctx: &mut SceneContext) {
+        let (initial, current) = (self.initial, ctx.settings);
+        if let Err(e) = Settings::update_saved(Path::new(SETTINGS_PATH), |saved| {
+            saved.apply_changes(&initial, &current);
+        }) {
+            eprintln!("Failed to save the settings: {}", e);
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => return Transition::Pop(1),
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => {
+                self.selected =
+                    (self.selected + SETTINGS_OPTIONS.len() - 1) % SETTINGS_OPTIONS.len();
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => self.selected = (self.selected + 1) % SETTINGS_OPTIONS.len(),
+            Event::KeyDown {
+                keycode:
+                    Some(key @ (Keycode::Left | Keycode::Right | Keycode::Return | Keycode::KpEnter)),
+                ..
+            } => match SETTINGS_OPTIONS[self.selected] {
+                SettingsOption::Difficulty if *key == Keycode::Left => {
+                    ctx.settings.difficulty = ctx.settings.difficulty.previous();
+                }
+                SettingsOption::Difficulty => {
+                    ctx.settings.difficulty = ctx.settings.difficulty.next()
+                }
+                SettingsOption::Sound => {
+                    ctx.settings.is_muted = !ctx.settings.is_muted;
+                    ctx.sounds.set_muted(ctx.settings.is_muted);
+                }
+                SettingsOption::Fullscreen => {
+                    ctx.settings.cycle_fullscreen(*key != Keycode::Left);
+                    ctx.apply_display_settings();
+                }
+                SettingsOption::Display => {
+                    // The selected display may have been disconnected.
+                    let count = display_count(&ctx.canvas) as u32;
+                    let display = connected_display(&ctx.canvas, ctx.settings.display);
+                    ctx.settings.display = if *key == Keycode::Left {
+                        (display + count - 1) % count
+                    } else {
+                        (display + 1) % count
+                    };
+                    ctx.apply_display_settings();
+                }
+                SettingsOption::VSync => ctx.settings.vsync = !ctx.settings.vsync,
+                SettingsOption::Back if matches!(key, Keycode::Return | Keycode::KpEnter) => {
+                    return Transition::Pop(1);
+                }
+                SettingsOption::Back => {}
+            },
+            _ => {}
+        }
+        Transition::None
+    }
+
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let labels: Vec<String> = SETTINGS_OPTIONS
+            .iter()
+            .map(|o| o.label(&ctx.settings))
+            .collect();
+        draw_menu(ctx, "SETTINGS", &labels, Some(self.selected))
+    }
+}
+
+/// The **HighScoresScene** struct is the scene showing the high scores,
+/// until the enter or escape key is pressed.
+pub struct HighScoresScene;
+
+impl Scene for HighScoresScene {
+    fn handle_event(&mut self, _ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape | Keycode::Return | Keycode::KpEnter),
+                ..
+            } => Transition::Pop(1),
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        let mut lines: Vec<String> = ctx
+            .high_scores
+            .scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                format!(
+                    "{}. {} - {} ({})",
+                    i + 1,
+                    s.score,
+                    s.opponent_score,
+                    s.difficulty
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(String::from("No game played yet!"));
+        }
+        lines.push(String::from("Back"));
+        let back = lines.len() - 1;
+        draw_menu(ctx, "HIGH SCORES", &lines, Some(back))
+    }
+}
+
+/// Draw a menu screen: its title on top and its options below it,
+/// the selected option being highlighted.
+fn draw_menu(
+    ctx: &mut SceneContext,
+    title: &str,
+    options: &[String],
+    selected: Option<usize>,
+) -> GameResult<()> {
+    let (gf, canvas, window) = (&ctx.gf, &mut ctx.canvas, &ctx.config.window);
+    canvas.set_draw_color(Color::BLACK);
+    canvas.clear();
+
+    let title_style = TextStyle::new(&gf.poetsen, TITLE_FONT_SIZE, Color::WHITE);
+    let title_layout = TextLayout::new((window.width / 2) as i32, SCREEN_PADDING, Anchor::Top);
+    let rect_title = gf.draw_text(canvas, title, &title_style, &title_layout)?;
+
+    for (i, option) in options.iter().enumerate() {
+        let color = if selected == Some(i) {
+            COLOR_SELECTED
+        } else {
+            Color::WHITE
+        };
+        let option_style = TextStyle::new(&gf.poetsen, OPTION_FONT_SIZE, color);
+        let option_layout = TextLayout::new(
+            (window.width / 2) as i32,
+            rect_title.bottom() + SCREEN_PADDING + (i as u32 * OPTION_HEIGHT) as i32,
+            Anchor::Top,
+        );
+        gf.draw_text(canvas, option, &option_style, &option_layout)?;
+    }
+    Ok(())
+}
@@ -2,53 +2,37 @@ use sdl2::pixels::Color;
 use sdl2::rect::Point;
 use sdl2::rect::Rect;
 
-use rand::random;
 use rand::Rng;
 
-use std::ops::RangeInclusive;
+use crate::config::GameConfig;
+use crate::vector::Vector;
 
-use crate::WINDOW_HEIGHT;
-use crate::WINDOW_WIDTH;
-
-/// The height of a **MissileBody** rectangle (in pixels).
+/// The default height of a **MissileBody** rectangle (in pixels).
 const MISSILE_HEIGHT: u32 = 5;
 
-/// The width of a **MissileBody** rectangle (in pixels).
+/// The default width of a **MissileBody** rectangle (in pixels).
 const MISSILE_WIDTH: u32 = 10;
 
-/// The speed of **Missile** entity and its components (in pixels).
-const MISSILE_SPEED: i64 = 2;
-
-/// The size used to build the triangle representing the **MissileHead**.
-/// It corresponds to the height of the triangle. For the sake of aesthetics,
-/// The value is the same as **MISSILE_HEIGHT** and **MISSILE_TAIL_SIZE**.
-const MISSILE_HEAD_SIZE: u32 = MISSILE_HEIGHT;
-
-/// The size used to build the triangles representing the **MissileTail**.
-/// It corresponds to the height of the triangles. For the sake of aesthetics,
-/// The value is the same as **MISSILE_HEIGHT** and **MISSILE_HEAD_SIZE**. 
-const MISSILE_TAIL_SIZE: u32 = MISSILE_HEIGHT;
+/// The default speed of **Missile** entity (in pixels per tick).
+pub const MISSILE_SPEED: f32 = 2.0;
 
-/// The color of the triangle representing the **MissileHead**.
-const MISSILE_HEAD_COLOR: Color = Color::RED;
+/// The default color of the triangle representing the **MissileHead**.
+pub const MISSILE_HEAD_COLOR: Color = Color::RED;
 
-/// The color of the rectangle representing the **MissileBody**.
-const MISSILE_BODY_COLOR: Color = Color::WHITE;
+/// The default color of the rectangle representing the **MissileBody**.
+pub const MISSILE_BODY_COLOR: Color = Color::WHITE;
 
-/// The color of the triangles representing the **MissileTail**. 
-const MISSILE_TAIL_COLOR: Color = Color::GREY;
+/// The default color of the triangles representing the **MissileTail**. 
+pub const MISSILE_TAIL_COLOR: Color = Color::GREY;
 
-/// The X axis range where a **Missile** can spawn.
-/// It's basically the whole screen's width.
-const MISSILE_SPAWN_RANGE_X: RangeInclusive<u32> = 0..=WINDOW_WIDTH;
+/// The height at the bottom of the screen where no **Missile** spawns
+/// by default, in order to let the spaceships spawn without being
+/// killed instantly.
+pub const MISSILE_SPAWN_BOTTOM_MARGIN: u32 = 200;
 
-/// The Y axis range where a **Missile** can spawn.
-/// It's from the top of the screen to the **Spaceship** area,
-/// in order to let them spawn without killing them instantly.
-const MISSILE_SPAWN_RANGE_Y: RangeInclusive<u32> = 0..=(WINDOW_HEIGHT - 200);
-
-/// Define the main direction of a missile and its components.
-/// A missile can only move in two directions: to the *left* or to the *right*.
+/// Define the main direction of a missile and its components: to the
+/// *left* or to the *right*. A missile may also drift up or down, but its
+/// components always point toward its main direction.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MissileDirection {
     Left,
@@ -56,19 +40,25 @@ pub enum MissileDirection {
 }
 
 /// The **Missile** entity that must be avoided in order to win 
-/// the game. The struct is comprised three substruct: 
-/// **MissileHead**, **MissileBody** and **MissileTail**.
+/// the game. The **Missile** is a position moved by a velocity at every
+/// tick, from which its three components are derived: **MissileHead**,
+/// **MissileBody** and **MissileTail**.
+#[derive(Clone)]
 pub struct Missile {
-    pub x: i32,
-    pub y: i32,
-    pub body: MissileBody,
-    pub tail: MissileTail,
-    pub head: MissileHead,
-    pub direction: MissileDirection,
+    /// The top left corner of the **MissileBody**.
+    pub position: Vector,
+    /// The distance covered at every tick.
+    pub velocity: Vector,
+    /// The size of the **Missile**, as a factor of the default size.
+    pub size: f32,
+    pub head_color: Color,
+    pub body_color: Color,
+    pub tail_color: Color,
 }
 
 /// The **MissileBody** struct represents the main part of a **Missile**.
 /// It is made of a single rectangular shape.
+#[derive(Clone)]
 pub struct MissileBody {
     pub rect: Rect,
     pub color: Color,
@@ -76,6 +66,7 @@ pub struct MissileBody {
 
 /// The **MissileTail** struct represents the side opposite to the **Missile**'s
 /// direction. It is made of two triangles on each sides of the **MissileBody**.
+#[derive(Clone)]
 pub struct MissileTail {
     pub top_triangle_x: [i16; 3],
     pub top_triangle_y: [i16; 3],
@@ -87,6 +78,7 @@ pub struct MissileTail {
 /// The **MissileHead** struct is the *top* of the **Missile**. Its orientation is
 /// the same as the **Missile**'s direction. It is made of a single triangle pointing
 /// toward the **Missile**'s direction.
+#[derive(Clone)]
 pub struct MissileHead {
     pub triangle_x: [i16; 3],
     pub triangle_y: [i16; 3],
@@ -94,102 +86,181 @@ pub struct MissileHead {
 }
 
 impl Missile {
-    pub fn new() -> Missile {
-        let mut rng = rand::thread_rng();
-        let pos_x = rng.gen_range(MISSILE_SPAWN_RANGE_X) as i32;
-        let pos_y = rng.gen_range(MISSILE_SPAWN_RANGE_Y) as i32;
+    /// Create a new **Missile** at a random position and with a random
+    /// direction, within the spawn ranges of the configuration. Its speed,
+    /// size and drift are random within the ranges of the missile variety.
+    /// All the randomness is drawn from the given generator, so the same
+    /// generator state always gives the same **Missile**.
+    pub fn new<R: Rng>(rng: &mut R, config: &GameConfig) -> Missile {
+        let pos_x = rng.gen_range(config.missile_spawn_range_x());
+        let pos_y = rng.gen_range(config.missile_spawn_range_y());
+        let is_left = rng.gen::<bool>();
+
+        let variety = &config.missile.variety;
+        let speed = match variety.speed_bands.as_slice() {
+            [] => config.missile.speed,
+            bands => {
+                let band = bands[rng.gen_range(0..bands.len())];
+                random_in(rng, band)
+            }
+        };
+        let size = random_in(rng, variety.size);
+        let drift = random_in(rng, [-variety.max_drift, variety.max_drift]);
 
-        let d: MissileDirection = if random::<u8>() % 2 == 0 {
+        Missile {
+            position: Vector::new(pos_x as f32, pos_y as f32),
+            velocity: Vector::new(if is_left { -speed } else { speed }, drift),
+            size,
+            head_color: config.missile.head_color,
+            body_color: config.missile.body_color,
+            tail_color: config.missile.tail_color,
+        }
+    }
+
+    /// The side the **Missile** is moving to.
+    pub fn direction(&self) -> MissileDirection {
+        if self.velocity.x < 0.0 {
             MissileDirection::Left
         } else {
             MissileDirection::Right
-        };
-        let r = Rect::new(pos_x, pos_y, MISSILE_WIDTH, MISSILE_HEIGHT);
-        let m = MissileBody::new(r);
-        let t = MissileTail::new(pos_x as i16, pos_y as i16, &d);
-        let h = MissileHead::new(pos_x as i16, pos_y as i16, &d);
-
-        Missile {
-            x: pos_x,
-            y: pos_y,
-            direction: d,
-            body: m,
-            tail: t,
-            head: h,
         }
     }
+
+    /// The rectangle of the **MissileBody** at the current position,
+    /// scaled to the size of the **Missile**.
+    fn body_rect(&self) -> Rect {
+        let (x, y) = self.position.to_pixel();
+        let scale = |length: u32| ((length as f32 * self.size).round() as u32).max(1);
+        Rect::new(x, y, scale(MISSILE_WIDTH), scale(MISSILE_HEIGHT))
+    }
+
+    /// The **MissileBody** at the current position.
+    pub fn body(&self) -> MissileBody {
+        MissileBody::new(self.body_rect(), self.body_color)
+    }
+
+    /// The **MissileHead** at the current position.
+    pub fn head(&self) -> MissileHead {
+        MissileHead::new(self.body_rect(), &self.direction(), self.head_color)
+    }
+
+    /// The **MissileTail** at the current position.
+    pub fn tail(&self) -> MissileTail {
+        MissileTail::new(self.body_rect(), &self.direction(), self.tail_color)
+    }
+
+    /// The convex shapes of the **Missile**'s components, given by their
+    /// vertices in order, used to compute the collisions.
+    pub fn polygons(&self) -> Vec<Vec<Point>> {
+        let (head, body, tail) = (self.head(), self.body(), self.tail());
+        vec![
+            triangle_points(&head.triangle_x, &head.triangle_y),
+            vec![
+                body.rect.top_left(),
+                body.rect.top_right(),
+                body.rect.bottom_right(),
+                body.rect.bottom_left(),
+            ],
+            triangle_points(&tail.top_triangle_x, &tail.top_triangle_y),
+            triangle_points(&tail.bot_triangle_x, &tail.bot_triangle_y),
+        ]
+    }
     
-    /// Update the **Missile** position.
-    pub fn update(&mut self) {
+    /// Update the **Missile** position, within the screen and the spawn
+    /// ranges of the configuration.
+    pub fn update(&mut self, config: &GameConfig) {
         // Check if the missile is going out of the screen.
-        self.check_screen_wrapping();
+        self.check_screen_wrapping(config.window.width);
+
+        self.position += self.velocity;
 
-        // Update Missile and its components coordinates according to its direction.
-        self.move_toward(&self.direction.clone());
-        self.body.move_toward(&self.direction);
-        self.head.move_toward(&self.direction);
-        self.tail.move_toward(&self.direction);
+        // Check if the missile drifted out of the spawn area.
+        let range_y = config.missile_spawn_range_y();
+        self.check_drift_bounce(*range_y.start() as f32, *range_y.end() as f32);
     }
     
     /// Check if a **Missile** is going out of the screen. If it is the case,
-    /// telepots all its components to the opposite side of the screen.
-    fn check_screen_wrapping(&mut self) {
-        if self.direction == MissileDirection::Left && self.x < 0 {
-            self.x = WINDOW_WIDTH as i32;
-            let r = Rect::new(self.x, self.y, MISSILE_WIDTH, MISSILE_HEIGHT);
-            self.body = MissileBody::new(r);
-            self.tail = MissileTail::new(self.x as i16, self.y as i16, &self.direction);
-            self.head = MissileHead::new(self.x as i16, self.y as i16, &self.direction);
-        } else if self.direction == MissileDirection::Right && self.x > WINDOW_WIDTH as i32 {
-            self.x = 0;
-            let r = Rect::new(self.x, self.y, MISSILE_WIDTH, MISSILE_HEIGHT);
-            self.body = MissileBody::new(r);
-            self.tail = MissileTail::new(self.x as i16, self.y as i16, &self.direction);
-            self.head = MissileHead::new(self.x as i16, self.y as i16, &self.direction);
+    /// telepots it to the opposite side of the screen.
+    fn check_screen_wrapping(&mut self, screen_width: u32) {
+        let direction = self.direction();
+        if direction == MissileDirection::Left && self.position.x < 0.0 {
+            self.position.x = screen_width as f32;
+        } else if direction == MissileDirection::Right && self.position.x > screen_width as f32 {
+            self.position.x = 0.0;
+        }
+    }
+
+    /// Check if a drifting **Missile** went out of the given vertical
+    /// range. If it is the case, it is put back on the edge of the range
+    /// and drifts back into it.
+    fn check_drift_bounce(&mut self, top: f32, bottom: f32) {
+        if self.velocity.y < 0.0 && self.position.y < top {
+            self.position.y = top;
+            self.velocity.y = -self.velocity.y;
+        } else if self.velocity.y > 0.0 && self.position.y > bottom {
+            self.position.y = bottom;
+            self.velocity.y = -self.velocity.y;
         }
     }
 }
 
+/// Draw a random value within the given range, without drawing anything
+/// if the range is a single value.
+fn random_in<R: Rng>(rng: &mut R, [start, end]: [f32; 2]) -> f32 {
+    if start < end {
+        rng.gen_range(start..=end)
+    } else {
+        start
+    }
+}
+
+/// The vertices of a triangle given by its coordinates.
+pub(crate) fn triangle_points(triangle_x: &[i16; 3], triangle_y: &[i16; 3]) -> Vec<Point> {
+    (0..3)
+        .map(|i| Point::new(triangle_x[i] as i32, triangle_y[i] as i32))
+        .collect()
+}
+
 impl MissileBody {
-    fn new(r: Rect) -> MissileBody {
+    fn new(r: Rect, color: Color) -> MissileBody {
         MissileBody {
             rect: r,
-            color: MISSILE_BODY_COLOR,
+            color,
         }
     }
 }
 
 impl MissileTail {
 
-    /// Create a new **MissileTail** struct with a given point corresponding
-    /// to the origin of the **MissileBody**. Moreover, a **MissileDirection**
-    /// is also given in order to know on which side of of the **Missile** the
-    /// tail will be attached.
-    pub fn new(origin_x: i16, origin_y: i16, direction: &MissileDirection) -> MissileTail {
+    /// Create a new **MissileTail** struct attached to the given rectangle
+    /// of the **MissileBody**. Moreover, a **MissileDirection** is also given
+    /// in order to know on which side of of the **Missile** the tail will be
+    /// attached. For the sake of aesthetics, the height of the triangles is
+    /// the same as the height of the body.
+    pub fn new(body: Rect, direction: &MissileDirection, color: Color) -> MissileTail {
+        let (origin_x, origin_y) = (body.x() as i16, body.y() as i16);
+        let (width, height) = (body.width() as i16, body.height() as i16);
+        let size = height;
         let top_triangle_xs: [i16; 3];
         let top_triangle_ys: [i16; 3];
         let bot_triangle_ys: [i16; 3];
         match direction {
             MissileDirection::Left => {
-                top_triangle_xs = [
-                    origin_x + (MISSILE_WIDTH - MISSILE_TAIL_SIZE) as i16,
-                    origin_x + MISSILE_WIDTH as i16,
-                    origin_x + MISSILE_WIDTH as i16,
-                ];
-                top_triangle_ys = [origin_y, origin_y, origin_y - MISSILE_TAIL_SIZE as i16];
+                top_triangle_xs = [origin_x + width - size, origin_x + width, origin_x + width];
+                top_triangle_ys = [origin_y, origin_y, origin_y - size];
                 bot_triangle_ys = [
-                    origin_y + MISSILE_HEIGHT as i16,
-                    origin_y + MISSILE_HEIGHT as i16,
-                    origin_y + (MISSILE_HEIGHT + MISSILE_TAIL_SIZE) as i16,
+                    origin_y + height,
+                    origin_y + height,
+                    origin_y + height + size,
                 ];
             }
             MissileDirection::Right => {
-                top_triangle_xs = [origin_x, origin_x, origin_x + MISSILE_TAIL_SIZE as i16];
-                top_triangle_ys = [origin_y, origin_y - MISSILE_TAIL_SIZE as i16, origin_y];
+                top_triangle_xs = [origin_x, origin_x, origin_x + size];
+                top_triangle_ys = [origin_y, origin_y - size, origin_y];
                 bot_triangle_ys = [
-                    origin_y + MISSILE_HEIGHT as i16,
-                    origin_y + (MISSILE_HEIGHT + MISSILE_TAIL_SIZE) as i16,
-                    origin_y + MISSILE_HEIGHT as i16,
+                    origin_y + height,
+                    origin_y + height + size,
+                    origin_y + height,
                 ];
             }
         }
@@ -198,160 +269,72 @@ impl MissileTail {
             top_triangle_y: top_triangle_ys,
             bot_triangle_x: top_triangle_xs, // same coordinates
             bot_triangle_y: bot_triangle_ys,
-            color: MISSILE_TAIL_COLOR,
+            color,
         }
     }
 }
 
 impl MissileHead {
     
-    /// Create a new **MissileHead** struct with a given point corresponding to
-    /// the origin of the **MissileBody** and a **MissileDirection** in order to
-    /// know on which side of the **Missile** to draw the head.
-    pub fn new(origin_x: i16, origin_y: i16, direction: &MissileDirection) -> MissileHead {
-        let triangle_xs: [i16; 3];
-        let triangle_ys: [i16; 3];
-
-        match direction {
-            MissileDirection::Left => {
-                triangle_xs = [origin_x, origin_x - MISSILE_HEAD_SIZE as i16, origin_x];
-                triangle_ys = [
-                    origin_y,
-                    origin_y + MISSILE_HEAD_SIZE as i16 / 2,
-                    origin_y + MISSILE_HEIGHT as i16,
-                ];
-            }
-            MissileDirection::Right => {
-                triangle_xs = [
-                    origin_x + MISSILE_WIDTH as i16,
-                    origin_x + (MISSILE_WIDTH + MISSILE_HEAD_SIZE) as i16,
-                    origin_x + MISSILE_WIDTH as i16,
-                ];
-                triangle_ys = [
-                    origin_y,
-                    origin_y + MISSILE_HEIGHT as i16 / 2,
-                    origin_y + MISSILE_HEIGHT as i16,
-                ];
-            }
-        }
+    /// Create a new **MissileHead** struct attached to the given rectangle
+    /// of the **MissileBody**, and a **MissileDirection** in order to know on
+    /// which side of the **Missile** to draw the head. For the sake of
+    /// aesthetics, the height of the triangle is the same as the height of
+    /// the body.
+    pub fn new(body: Rect, direction: &MissileDirection, color: Color) -> MissileHead {
+        let (origin_x, origin_y) = (body.x() as i16, body.y() as i16);
+        let (width, height) = (body.width() as i16, body.height() as i16);
+        let size = height;
+        let triangle_xs = match direction {
+            MissileDirection::Left => [origin_x, origin_x - size, origin_x],
+            MissileDirection::Right => [
+                origin_x + width,
+                origin_x + width + size,
+                origin_x + width,
+            ],
+        };
+        let triangle_ys = [origin_y, origin_y + size / 2, origin_y + height];
         MissileHead {
             triangle_x: triangle_xs,
             triangle_y: triangle_ys,
-            color: MISSILE_HEAD_COLOR,
+            color,
         }
     }
 }
 
-/// Trait used to return a **Missile** and its components as a vector of points.
-/// The points can then be used to compute the collisions with another object, like
-/// a **Spaceship**.
-pub trait ToPoints {
-    fn points(&self) -> Vec<Point>;
-}
-
-impl ToPoints for Missile {
-    fn points(&self) -> Vec<Point> {
-        let mut result = Vec::new();
-        result.append(&mut self.head.points());
-        result.append(&mut self.body.points());
-        result.append(&mut self.tail.points());
-        result
-    }
-}
-
-impl ToPoints for MissileHead {
-    fn points(&self) -> Vec<Point> {
-        vec![
-            Point::new(self.triangle_x[0] as i32, self.triangle_y[0] as i32),
-            Point::new(self.triangle_x[1] as i32, self.triangle_y[1] as i32),
-            Point::new(self.triangle_x[2] as i32, self.triangle_y[2] as i32),
-        ]
-    }
-}
-
-impl ToPoints for MissileBody {
-    fn points(&self) -> Vec<Point> {
-        vec![
-            Point::new(self.rect.x(), self.rect.y()),
-            Point::new(self.rect.x() + self.rect.width() as i32, self.rect.y()),
-            Point::new(self.rect.x(), self.rect.y() + self.rect.height() as i32),
-            Point::new(
-                self.rect.x() + self.rect.width() as i32,
-                self.rect.y() + self.rect.height() as i32,
-            ),
-        ]
-    }
-}
-
-impl ToPoints for MissileTail {
-    fn points(&self) -> Vec<Point> {
-        vec![
-            Point::new(self.top_triangle_x[0] as i32, self.top_triangle_y[0] as i32),
-            Point::new(self.bot_triangle_x[1] as i32, self.top_triangle_y[1] as i32),
-            Point::new(self.bot_triangle_x[2] as i32, self.top_triangle_y[2] as i32),
-            Point::new(self.bot_triangle_x[0] as i32, self.bot_triangle_y[0] as i32),
-            Point::new(self.bot_triangle_x[1] as i32, self.bot_triangle_y[1] as i32),
-            Point::new(self.bot_triangle_x[2] as i32, self.bot_triangle_y[2] as i32),
-        ]
-    }
-}
-
-/// Define the movements a **Missile** and its components can make.
-/// The movements depends on the values defined by **MissileDirection** enum.
-pub trait MissileMovement {
-    fn move_toward(&mut self, direction: &MissileDirection) -> ();   
-}
-
-impl MissileMovement for Missile {
-    fn move_toward(&mut self, direction: &MissileDirection) {
-        match direction {
-            MissileDirection::Left => {
-                self.x -= MISSILE_SPEED as i32;
-            }
-            MissileDirection::Right => {
-                self.x += MISSILE_SPEED as i32;
-            }
-        }
-    }    
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_bounce_keeps_missile_in_spawn_range() {
+        let config = GameConfig::default();
+        let range_y = config.missile_spawn_range_y();
+        let (top, bottom) = (*range_y.start() as f32, *range_y.end() as f32);
+        let mut missile = Missile {
+            position: Vector::new(100.0, bottom - 1.0),
+            velocity: Vector::new(MISSILE_SPEED, 3.0),
+            size: 1.0,
+            head_color: MISSILE_HEAD_COLOR,
+            body_color: MISSILE_BODY_COLOR,
+            tail_color: MISSILE_TAIL_COLOR,
+        };
 
-impl MissileMovement for MissileHead {
-    fn move_toward(&mut self, direction: &MissileDirection) {
-        match direction {
-            MissileDirection::Left => {
-                self.triangle_x = self.triangle_x.map(|v| v - MISSILE_SPEED as i16);
-            }
-            MissileDirection::Right => {
-                self.triangle_x = self.triangle_x.map(|v| v + MISSILE_SPEED as i16);
+        let mut bounces = 0;
+        for _ in 0..1000 {
+            let was_going_down = missile.velocity.y > 0.0;
+            missile.update(&config);
+            assert!(
+                (top..=bottom).contains(&missile.position.y),
+                "{} out of {}..={}",
+                missile.position.y,
+                top,
+                bottom
+            );
+            if was_going_down != (missile.velocity.y > 0.0) {
+                bounces += 1;
             }
         }
+        assert!(bounces >= 2);
     }
 }
-
-impl MissileMovement for MissileBody {
-    fn move_toward(&mut self, direction: &MissileDirection) {
-        match direction {
-            MissileDirection::Left => {
-                self.rect.set_x(self.rect.x() - MISSILE_SPEED as i32);
-            }
-            MissileDirection::Right => {
-                self.rect.set_x(self.rect.x() + MISSILE_SPEED as i32);
-            }
-        }
-    }    
-}
-
-impl MissileMovement for MissileTail {
-    fn move_toward(&mut self, direction: &MissileDirection) {
-        match direction {
-            MissileDirection::Left => {
-                self.top_triangle_x = self.top_triangle_x.map(|v| v - MISSILE_SPEED as i16);
-                self.bot_triangle_x = self.bot_triangle_x.map(|v| v - MISSILE_SPEED as i16);
-            }
-            MissileDirection::Right => {
-                self.top_triangle_x = self.top_triangle_x.map(|v| v + MISSILE_SPEED as i16);
-                self.bot_triangle_x = self.bot_triangle_x.map(|v| v + MISSILE_SPEED as i16);
-            }
-        }
-    }    
-}
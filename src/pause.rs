@@ -0,0 +1,136 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use crate::controller::ControllerKind;
+use crate::error::GameResult;
+use crate::game_event::PlayingScene;
+use crate::scene::*;
+use crate::view::draw_pause_menu;
+
+/// The options of the pause menu, in the order they are displayed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseOption {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseOption {
+    /// The text displayed for the option in the pause menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseOption::Resume => "Resume",
+            PauseOption::Restart => "Restart",
+            PauseOption::Quit => "Quit",
+        }
+    }
+}
+
+/// All the **PauseOption** values, in the order they are displayed.
+pub const PAUSE_OPTIONS: [PauseOption; 3] =
+    [PauseOption::Resume, PauseOption::Restart, PauseOption::Quit];
+
+/// The **PauseMenu** struct keeps track of the option currently
+/// selected while the game is paused.
+pub struct PauseMenu {
+    pub selected: usize,
+}
+
+impl PauseMenu {
+    /// Create a new **PauseMenu** with the first option (resume) selected.
+    pub fn new() -> PauseMenu {
+        PauseMenu { selected: 0 }
+    }
+
+    /// Select the option above the current one, wrapping to the last one.
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + PAUSE_OPTIONS.len() - 1) % PAUSE_OPTIONS.len();
+    }
+
+    /// Select the option below the current one, wrapping to the first one.
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % PAUSE_OPTIONS.len();
+    }
+
+    /// The option currently selected.
+    pub fn selected_option(&self) -> PauseOption {
+        PAUSE_OPTIONS[self.selected]
+    }
+}
+
+impl Default for PauseMenu {
+    fn default() -> Self {
+        PauseMenu::new()
+    }
+}
+
+/// The **PausedScene** struct is the scene shown over a paused match.
+/// The match below is frozen, as only the top scene is updated.
+pub struct PausedScene {
+    pause_menu: PauseMenu,
+    controllers: (ControllerKind, ControllerKind),
+}
+
+impl PausedScene {
+    /// Create the pause scene of a match played by the given controllers,
+    /// which are used again if the match is restarted.
+    pub fn new(controllers: (ControllerKind, ControllerKind)) -> PausedScene {
+        PausedScene {
+            pause_menu: PauseMenu::new(),
+            controllers,
+        }
+    }
+}
+
+impl Scene for PausedScene {
+    /// Navigate through the pause menu, confirm the selected option or
+    /// resume the game. Restarting replaces the paused match by a new one,
+    /// and quitting goes back to the main menu.
+    fn handle_event(&mut self, ctx: &mut SceneContext, event: &Event) -> Transition {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape | Keycode::P),
+                repeat: false,
+                ..
+            } => Transition::Pop(1),
+            Event::KeyDown {
+                keycode: Some(Keycode::Up),
+                ..
+            } => {
+                self.pause_menu.select_previous();
+                Transition::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Down),
+                ..
+            } => {
+                self.pause_menu.select_next();
+                Transition::None
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::Return | Keycode::KpEnter),
+                ..
+            } => match self.pause_menu.selected_option() {
+                PauseOption::Resume => Transition::Pop(1),
+                PauseOption::Restart => {
+                    Transition::Replace(2, Box::new(PlayingScene::new(ctx, self.controllers)))
+                }
+                PauseOption::Quit => Transition::Pop(2),
+            },
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        draw_pause_menu(
+            &mut ctx.canvas,
+            &ctx.config.window,
+            &self.pause_menu,
+            &ctx.gf,
+        )
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,236 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::keyboard::Mod;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::EventPump;
+
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::config::GameConfig;
+use crate::display::apply_display_settings;
+use crate::error::GameResult;
+use crate::game_event::MatchOptions;
+use crate::high_scores::HighScores;
+use crate::settings::Settings;
+use crate::settings::SETTINGS_PATH;
+use crate::GameFont;
+use crate::GameSFX;
+
+/// The shortest time between two frames, so the game doesn't draw more
+/// frames than needed when they are not synchronized with the display
+/// (240 frames per second).
+const MIN_FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 240);
+
+/// The **SceneContext** struct gathers what is shared by all the scenes:
+/// the SDL front end (canvas, events, fonts and sounds), the configuration
+/// and the data of the players (settings and high scores).
+pub struct SceneContext {
+    pub canvas: Canvas<Window>,
+    pub event_pump: EventPump,
+    pub gf: GameFont,
+    pub sounds: GameSFX,
+    pub config: GameConfig,
+    pub settings: Settings,
+    pub high_scores: HighScores,
+    pub match_options: MatchOptions,
+}
+
+impl SceneContext {
+    /// Show the window as set in the current settings. The window stays
+    /// as it was if it fails.
+    pub fn apply_display_settings(&mut self) {
+        if let Err(e) = apply_display_settings(&mut self.canvas, &self.settings) {
+            eprintln!("Failed to apply the display settings: {}", e);
+        }
+    }
+
+    /// Switch between the window and fullscreen, and save the choice.
+    pub fn toggle_fullscreen(&mut self) {
+        self.settings.is_fullscreen = !self.settings.is_fullscreen;
+        self.apply_display_settings();
+
+        let is_fullscreen = self.settings.is_fullscreen;
+        if let Err(e) = Settings::update_saved(Path::new(SETTINGS_PATH), |settings| {
+            settings.is_fullscreen = is_fullscreen;
+        }) {
+            eprintln!("Failed to save the settings: {}", e);
+        }
+    }
+}
+
+/// What the **SceneManager** does after a scene handled an event or
+/// was updated.
+pub enum Transition {
+    /// Stay on the current scene.
+    None,
+    /// Show a new scene on top of the current one.
+    Push(Box<dyn Scene>),
+    /// Remove the given amount of scenes, going back to the one below.
+    Pop(usize),
+    /// Remove the given amount of scenes, then show a new scene instead.
+    Replace(usize, Box<dyn Scene>),
+    /// Remove all the scenes, which ends the game.
+    Quit,
+}
+
+/// A **Scene** is a screen of the game (disclaimer, menu, match...).
+/// The scenes are stacked by the **SceneManager**: only the top one
+/// handles the events and is updated, but the scenes below an overlay
+/// are still drawn.
+pub trait Scene {
+    /// Called when the scene is added to the stack.
+    fn enter(&mut self, _ctx: &mut SceneContext) {}
+
+    /// Called when the scene is removed from the stack.
+    fn exit(&mut self, _ctx: &mut SceneContext) {}
+
+    /// Handle an event of the window. Closing the window and toggling
+    /// fullscreen (F11 or Alt+Enter) are already handled by the
+    /// **SceneManager**.
+    fn handle_event(&mut self, _ctx: &mut SceneContext, _event: &Event) -> Transition {
+        Transition::None
+    }
+
+    /// Advance the scene by the time elapsed since the previous frame.
+    fn update(&mut self, _ctx: &mut SceneContext, _elapsed: Duration) -> Transition {
+        Transition::None
+    }
+
+    /// Draw the scene, without presenting the canvas.
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()>;
+
+    /// Whether the scene is drawn over the scene below it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// The **SceneManager** struct drives the stack of scenes with a single
+/// loop: every frame, the events are polled, then the top scene is
+/// updated and the visible scenes are drawn. The frames are drawn at the
+/// display's refresh rate (with vsync), and each scene advances by the
+/// time elapsed since the previous frame. The window stays responsive
+/// whatever the scene, and closing it ends the game.
+pub struct SceneManager {
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneManager {
+    /// Create a new **SceneManager** with the given scenes, the last one
+    /// being on top.
+    pub fn new(scenes: Vec<Box<dyn Scene>>) -> SceneManager {
+        SceneManager { scenes }
+    }
+
+    /// Run the main loop until there is no scene left. The loop stops at
+    /// the first scene failing to be drawn.
+    pub fn run(&mut self, ctx: &mut SceneContext) -> GameResult<()> {
+        for scene in &mut self.scenes {
+            scene.enter(ctx);
+        }
+
+        let mut last_frame = Instant::now();
+        while !self.scenes.is_empty() {
+            let frame_start = Instant::now();
+
+            let events: Vec<Event> = ctx.event_pump.poll_iter().collect();
+            for event in events {
+                let transition = match event {
+                    Event::Quit { .. } => Transition::Quit,
+                    _ if is_fullscreen_toggle(&event) => {
+                        ctx.toggle_fullscreen();
+                        Transition::None
+                    }
+                    _ => self.top().handle_event(ctx, &event),
+                };
+                self.apply(ctx, transition);
+                if self.scenes.is_empty() {
+                    return Ok(());
+                }
+            }
+
+            let transition = self.top().update(ctx, frame_start - last_frame);
+            last_frame = frame_start;
+            self.apply(ctx, transition);
+
+            self.render(ctx)?;
+
+            if let Some(remaining) = MIN_FRAME_DURATION.checked_sub(frame_start.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+        Ok(())
+    }
+
+    fn top(&mut self) -> &mut Box<dyn Scene> {
+        self.scenes.last_mut().expect("no scene to show!")
+    }
+
+    fn apply(&mut self, ctx: &mut SceneContext, transition: Transition) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.push(ctx, scene),
+            Transition::Pop(count) => self.pop(ctx, count),
+            Transition::Replace(count, scene) => {
+                self.pop(ctx, count);
+                self.push(ctx, scene);
+            }
+            Transition::Quit => self.pop(ctx, self.scenes.len()),
+        }
+    }
+
+    fn push(&mut self, ctx: &mut SceneContext, mut scene: Box<dyn Scene>) {
+        scene.enter(ctx);
+        self.scenes.push(scene);
+    }
+
+    fn pop(&mut self, ctx: &mut SceneContext, count: usize) {
+        for _ in 0..count {
+            if let Some(mut scene) = self.scenes.pop() {
+                scene.exit(ctx);
+            }
+        }
+    }
+
+    /// Draw the top scene, and the scenes below it as long as they are
+    /// covered by overlays.
+    fn render(&self, ctx: &mut SceneContext) -> GameResult<()> {
+        if self.scenes.is_empty() {
+            return Ok(());
+        }
+        let first = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in &self.scenes[first..] {
+            scene.render(ctx)?;
+        }
+        ctx.canvas.present();
+        ctx.gf.end_frame();
+        Ok(())
+    }
+}
+
+/// Whether the event is a key press switching between the window and
+/// fullscreen: F11, or Enter while holding Alt.
+fn is_fullscreen_toggle(event: &Event) -> bool {
+    match event {
+        Event::KeyDown {
+            keycode: Some(Keycode::F11),
+            repeat: false,
+            ..
+        } => true,
+        Event::KeyDown {
+            keycode: Some(Keycode::Return | Keycode::KpEnter),
+            keymod,
+            repeat: false,
+            ..
+        } => keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        _ => false,
+    }
+}
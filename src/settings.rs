@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::ai::Difficulty;
+
+/// The path of the file keeping the settings, relative to the
+/// working directory.
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+/// How the game is shown when it is in fullscreen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FullscreenMode {
+    /// A borderless window covering the whole display, keeping its
+    /// resolution. Switching to other windows is instant.
+    #[default]
+    Borderless,
+    /// The display's resolution is changed to the one of the window.
+    Exclusive,
+}
+
+impl FullscreenMode {
+    /// The name of the mode, as displayed in the settings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FullscreenMode::Borderless => "Borderless",
+            FullscreenMode::Exclusive => "Exclusive",
+        }
+    }
+}
+
+impl fmt::Display for FullscreenMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl FromStr for FullscreenMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "borderless" => Ok(FullscreenMode::Borderless),
+            "exclusive" => Ok(FullscreenMode::Exclusive),
+            _ => Err(format!(
+                "unknown fullscreen mode {} (expected borderless or exclusive)",
+                s
+            )),
+        }
+    }
+}
+
+/// The **Settings** struct contains the preferences of the players,
+/// which can be changed from the settings screen of the main menu.
+/// They are kept from one launch of the game to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The difficulty of the CPU opponent.
+    pub difficulty: Difficulty,
+    /// Whether the game is played without any sound.
+    pub is_muted: bool,
+    /// Whether the disclaimer was already shown on a previous launch.
+    pub has_seen_disclaimer: bool,
+    /// Whether the game is played in fullscreen.
+    pub is_fullscreen: bool,
+    /// How the game is shown in fullscreen.
+    pub fullscreen_mode: FullscreenMode,
+    /// The index of the display (monitor) the game is shown on, starting at 0.
+    pub display: u32,
+    /// Whether the frames are synchronized with the display's refresh
+    /// rate. It is applied on the next launch.
+    pub vsync: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            difficulty: Difficulty::default(),
+            is_muted: false,
+            has_seen_disclaimer: false,
+            is_fullscreen: false,
+            fullscreen_mode: FullscreenMode::default(),
+            display: 0,
+            vsync: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Load the settings from the given TOML file. The default settings
+    /// are used if the file doesn't exist.
+    pub fn load(path: &Path) -> io::Result<Settings> {
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save the settings to the given TOML file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)
+    }
+
+    /// Switch to the next way of showing the game (windowed, borderless
+    /// fullscreen, exclusive fullscreen), or to the previous one.
+    pub fn cycle_fullscreen(&mut self, is_forward: bool) {
+        let (is_fullscreen, fullscreen_mode) = match (self.is_fullscreen, self.fullscreen_mode) {
+            (false, _) if is_forward => (true, FullscreenMode::Borderless),
+            (false, _) => (true, FullscreenMode::Exclusive),
+            (true, FullscreenMode::Borderless) if is_forward => (true, FullscreenMode::Exclusive),
+            (true, FullscreenMode::Exclusive) if !is_forward => (true, FullscreenMode::Borderless),
+            (true, mode) => (false, mode),
+        };
+        self.is_fullscreen = is_fullscreen;
+        self.fullscreen_mode = fullscreen_mode;
+    }
+
+    /// Copy the settings which differ between the two given ones (for
+    /// example before and after the settings screen), keeping the others.
+    pub fn apply_changes(&mut self, before: &Settings, after: &Settings) {
+        if before.difficulty != after.difficulty {
+            self.difficulty = after.difficulty;
+        }
+        if before.is_muted != after.is_muted {
+            self.is_muted = after.is_muted;
+        }
+        if (before.is_fullscreen, before.fullscreen_mode)
+            != (after.is_fullscreen, after.fullscreen_mode)
+        {
+            self.is_fullscreen = after.is_fullscreen;
+            self.fullscreen_mode = after.fullscreen_mode;
+        }
+        if before.display != after.display {
+            self.display = after.display;
+        }
+        if before.vsync != after.vsync {
+            self.vsync = after.vsync;
+        }
+    }
+
+    /// Change some of the settings saved in the given TOML file, keeping
+    /// the other saved values (the current ones may come from the
+    /// command-line options instead).
+    pub fn update_saved(path: &Path, update: impl FnOnce(&mut Settings)) -> io::Result<()> {
+        let mut saved = Settings::load(path)?;
+        update(&mut saved);
+        saved.save(path)
+    }
+}
@@ -1,114 +1,164 @@
 use sdl2::pixels::Color;
+use sdl2::rect::Point;
 use sdl2::rect::Rect;
 
 use std::time::Duration;
-use std::time::Instant;
 
+use crate::config::SpaceshipConfig;
+use crate::game_clock::GameClock;
+use crate::game_state::SpaceshipAction;
 use crate::missile::*;
-use crate::WINDOW_HEIGHT;
-use crate::WINDOW_WIDTH;
-
-const SPACESHIP_SPEED: i64 = 8;
-const SPACESHIP_BODY_WIDTH: u32 = 25;
-const SPACESHIP_BODY_HEIGHT: u32 = 60;
-const SPACESHIP_BODY_COLOR: Color = Color::WHITE;
-const SPACESHIP_PORTHOLE_COLOR: Color = Color::BLUE;
+use crate::vector::Vector;
+
+pub const SPACESHIP_SPEED: f32 = 8.0;
+pub const SPACESHIP_BODY_WIDTH: u32 = 25;
+pub const SPACESHIP_BODY_HEIGHT: u32 = 60;
+pub const SPACESHIP_BODY_COLOR: Color = Color::WHITE;
+pub const SPACESHIP_PORTHOLE_COLOR: Color = Color::BLUE;
 const SPACESHIP_HEAD_SIZE: u32 = SPACESHIP_BODY_WIDTH;
-const SPACESHIP_HEAD_COLOR: Color = Color::RED;
+pub const SPACESHIP_HEAD_COLOR: Color = Color::RED;
 pub const SPACESHIP_TAIL_SIZE: i16 = (SPACESHIP_BODY_WIDTH / 4 * 3) as i16;
-const SPACESHIP_TAIL_COLOR: Color = Color::RED;
-pub const SPACESHIP_P1_X: i32 = (WINDOW_WIDTH / 4 - SPACESHIP_BODY_WIDTH / 2) as i32;
-pub const SPACESHIP_P1_Y: i32 = (WINDOW_HEIGHT - SPACESHIP_BODY_HEIGHT) as i32;
-pub const SPACESHIP_P2_X: i32 = SPACESHIP_P1_X + (WINDOW_WIDTH / 2) as i32;
-pub const SPACESHIP_P2_Y: i32 = SPACESHIP_P1_Y;
-const SPACESHIP_DEATH_TIME: Duration = Duration::new(1, 0);
+pub const SPACESHIP_TAIL_COLOR: Color = Color::RED;
+pub const SPACESHIP_DEATH_TIME: Duration = Duration::new(1, 0);
 
 /// The **Spaceship** struct is the main entity managed by the player
-/// and the computer. The struct is comprised of three substructs:
+/// and the computer. The **Spaceship** is a position moved by a velocity
+/// at every tick, from which its three components are derived:
 /// **SpaceshipHead**, **SpaceshipBody** and **SpaceshipTail**.
+#[derive(Clone)]
 pub struct Spaceship {
-    pub body: SpaceshipBody,
-    pub head: SpaceshipHead,
-    pub tail: SpaceshipTail,
+    /// The top left corner of the **SpaceshipBody**.
+    pub position: Vector,
+    /// The distance covered at every tick.
+    pub velocity: Vector,
+    pub body_color: Color,
+    pub porthole_color: Color,
+    pub head_color: Color,
+    pub tail_color: Color,
     pub is_alive: bool,
-    pub death_instant: Option<Instant>,
-}
-
-/// This trait defines the movements for a **Spaceship** and its 
-/// comprised components. The only two movements allowed are upward
-/// and downward.
-pub trait SpaceshipMovement {
-    
-    /// Move towards the top of the window.
-    fn move_upward(&mut self) -> ();
-    
-    /// Move towards the bottom of the window.
-    fn move_downward(&mut self) -> ();    
+    pub death_time: Option<Duration>,
 }
 
 impl Spaceship {
     
-    /// Create a new **Spaceship** object on the given coordinates.
-    pub fn new(pos_x: i32, pos_y: i32) -> Spaceship {
+    /// Create a new **Spaceship** object on the given coordinates, with
+    /// the colors of the configuration.
+    pub fn new(pos_x: i32, pos_y: i32, config: &SpaceshipConfig) -> Spaceship {
         Spaceship {
-            body: SpaceshipBody::new(pos_x, pos_y, SPACESHIP_BODY_WIDTH, SPACESHIP_BODY_HEIGHT),
-            head: SpaceshipHead::new(pos_x as i16, pos_y as i16),
-            tail: SpaceshipTail::new(pos_x as i16, pos_y as i16),
+            position: Vector::new(pos_x as f32, pos_y as f32),
+            velocity: Vector::ZERO,
+            body_color: config.body_color,
+            porthole_color: config.porthole_color,
+            head_color: config.head_color,
+            tail_color: config.tail_color,
             is_alive: true,
-            death_instant: Some(Instant::now()),
+            death_time: None,
         }
     }
+
+    /// The **SpaceshipBody** at the current position.
+    pub fn body(&self) -> SpaceshipBody {
+        let (x, y) = self.position.to_pixel();
+        let mut body = SpaceshipBody::new(x, y, SPACESHIP_BODY_WIDTH, SPACESHIP_BODY_HEIGHT);
+        body.body_color = self.body_color;
+        body.porthole_color = self.porthole_color;
+        body
+    }
+
+    /// The **SpaceshipHead** at the current position.
+    pub fn head(&self) -> SpaceshipHead {
+        let (x, y) = self.position.to_pixel();
+        let mut head = SpaceshipHead::new(x as i16, y as i16);
+        head.color = self.head_color;
+        head
+    }
+
+    /// The **SpaceshipTail** at the current position.
+    pub fn tail(&self) -> SpaceshipTail {
+        let (x, y) = self.position.to_pixel();
+        let mut tail = SpaceshipTail::new(x as i16, y as i16);
+        tail.color = self.tail_color;
+        tail
+    }
+
+    /// Set the velocity of the **Spaceship** for the given action: upward
+    /// or downward at the given speed, unless it would go over the bottom
+    /// edge of a screen of the given height.
+    pub fn steer(&mut self, action: SpaceshipAction, speed: f32, screen_height: u32) {
+        self.velocity = match action {
+            SpaceshipAction::Upward => Vector::new(0.0, -speed),
+            SpaceshipAction::Downward if self.can_move_downward(screen_height) => {
+                Vector::new(0.0, speed)
+            }
+            SpaceshipAction::Downward | SpaceshipAction::Idle => Vector::ZERO,
+        };
+    }
+
+    /// Move the **Spaceship** by its velocity.
+    pub fn update(&mut self) {
+        self.position += self.velocity;
+    }
     
-    /// Set is_alive bool to false and register the death **Instant**. 
-    pub fn die(&mut self) {
+    /// Set is_alive bool to false and register the game time of death.
+    pub fn die(&mut self, clock: &GameClock) {
         self.is_alive = false;
-        self.death_instant = Some(Instant::now());
+        self.death_time = Some(clock.elapsed());
     }
 
     /// Check if the **Spaceship** can move downward (towards the bottom of
-    /// the screen). Return false if the lower part of the **Spaceship** 
-    /// (**SpaceshipTail**) is going to go over the edge of the screen. 
-    pub fn can_move_downward(&self) -> bool {
-        SPACESHIP_HEAD_SIZE + self.body.rect.y() as u32 + 2 * SPACESHIP_TAIL_SIZE as u32
-            <= WINDOW_HEIGHT
+    /// a screen of the given height). Return false if the lower part of the
+    /// **Spaceship** (**SpaceshipTail**) is going to go over the edge of the
+    /// screen.
+    pub fn can_move_downward(&self, screen_height: u32) -> bool {
+        (SPACESHIP_HEAD_SIZE + 2 * SPACESHIP_TAIL_SIZE as u32) as f32 + self.position.y
+            <= screen_height as f32
     }
 
     /// Check if the **Spaceship** can respawn in the game after it was
     /// killed. The two conditions for the respawn is to be dead and
-    /// if enough time has passed since the time of death (**death_instant**).
-    pub fn can_respawn(&self) -> bool {
+    /// if the given respawn delay has passed since the time of death
+    /// (**death_time**).
+    pub fn can_respawn(&self, clock: &GameClock, respawn_delay: Duration) -> bool {
         !self.is_alive
-            && self
-                .death_instant
-                .expect("no last death registered!")
-                .elapsed()
-                .as_secs()
-                > SPACESHIP_DEATH_TIME.as_secs()
+            && clock.elapsed_since(self.death_time.expect("no last death registered!"))
+                >= respawn_delay
+    }
+
+    /// The convex shapes of the **Spaceship**'s components, given by their
+    /// vertices in order, used to compute the collisions. The portholes
+    /// are within the body.
+    pub fn polygons(&self) -> Vec<Vec<Point>> {
+        let (head, body, tail) = (self.head(), self.body(), self.tail());
+        vec![
+            triangle_points(&head.triangle_x, &head.triangle_y),
+            vec![
+                body.rect.top_left(),
+                body.rect.top_right(),
+                body.rect.bottom_right(),
+                body.rect.bottom_left(),
+            ],
+            triangle_points(&tail.left_triangle_x, &tail.left_triangle_y),
+            triangle_points(&tail.right_triangle_x, &tail.right_triangle_y),
+        ]
     }
 
     /// Check if a collision occurs between a **Spaceship** and a **Missile**.
-    /// The collision test is applied on every points of **Missile** being
-    /// within any part of the **Spaceship** and its components.
+    /// The collision test is applied on the shapes of every component of
+    /// the **Missile** overlapping any component of the **Spaceship**, so
+    /// missiles of any size collide.
     pub fn collide_with(&self, missile: &Missile) -> bool {
-        let mut head_collision = false;
-        let mut body_collision = false;
-        let mut tail_collision = false;
-
-        let m_points = missile.points();
-        let mut h_i = 0;
-        while !head_collision && !body_collision && !tail_collision && h_i < m_points.len() {
-            head_collision = self.head.is_point_within(m_points[h_i].x, m_points[h_i].y);
-            body_collision = self.body.is_point_within(m_points[h_i].x, m_points[h_i].y);
-            tail_collision = self.tail.is_point_within(m_points[h_i].x, m_points[h_i].y);
-            h_i += 1;
-        }
-
-        head_collision || body_collision || tail_collision
+        let m_polygons = missile.polygons();
+        self.polygons().iter().any(|polygon| {
+            m_polygons
+                .iter()
+                .any(|m_polygon| do_polygons_overlap(polygon, m_polygon))
+        })
     }
 }
 
 /// The **SpaceshipBody** struct is the middle part of **Spaceship**.
 /// Its shape is rectangular and it contains two portholes.
+#[derive(Clone)]
 pub struct SpaceshipBody {
     pub rect: Rect,
     pub body_color: Color,
@@ -137,15 +187,11 @@ impl SpaceshipBody {
             porthole_color: SPACESHIP_PORTHOLE_COLOR,
         }
     }
-
-    pub fn is_point_within(&self, x: i32, y: i32) -> bool {
-        (self.rect.x() <= x && self.rect.x() + self.rect.width() as i32 >= x)
-            && (self.rect.y() <= y && self.rect.y() + self.rect.height() as i32 >= y)
-    }
 }
 
 /// The **SpaceshipHead** struct is the top part of **Spaceship**.
 /// its shape is made of one triangle.
+#[derive(Clone)]
 pub struct SpaceshipHead {
     pub triangle_x: [i16; 3],
     pub triangle_y: [i16; 3],
@@ -164,14 +210,11 @@ impl SpaceshipHead {
             color: SPACESHIP_HEAD_COLOR,
         }
     }
-
-    pub fn is_point_within(&self, x: i32, y: i32) -> bool {
-        is_point_within_triangle(&self.triangle_x, &self.triangle_y, x, y)
-    }
 }
 
 /// The **SpaceshipTail** struct represents the lower part of a **Spaceship**.
 /// It is made of two triangles located at lower sides of the **SpaceshipBody**.
+#[derive(Clone)]
 pub struct SpaceshipTail {
     pub left_triangle_x: [i16; 3],
     pub left_triangle_y: [i16; 3],
@@ -202,91 +245,109 @@ impl SpaceshipTail {
             color: SPACESHIP_TAIL_COLOR,
         }
     }
-
-    pub fn is_point_within(&self, x: i32, y: i32) -> bool {
-        is_point_within_triangle(&self.left_triangle_x, &self.left_triangle_y, x, y)
-            || is_point_within_triangle(&self.right_triangle_x, &self.right_triangle_y, x, y)
-    }
 }
 
-fn triangle_area(x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32) -> f64 {
-    let ab = vec![x2 - x1, y2 - y1];
-    let ac = vec![x3 - x1, y3 - y1];
-    let cross_product = ab[0] * ac[1] - ab[1] * ac[0];
-    (cross_product as f64 / 2.0).abs()
+/// Check if two convex polygons, given by their vertices in order, overlap
+/// (touching polygons do). According to the separating axis theorem, they
+/// don't if one of their edges separates them.
+pub fn do_polygons_overlap(a: &[Point], b: &[Point]) -> bool {
+    !has_separating_edge(a, b) && !has_separating_edge(b, a)
 }
 
-fn is_point_within_triangle(triangle_x: &[i16; 3], triangle_y: &[i16; 3], x: i32, y: i32) -> bool {
-    let xs = [
-        triangle_x[0] as i32,
-        triangle_x[1] as i32,
-        triangle_x[2] as i32,
-    ];
-    let ys = [
-        triangle_y[0] as i32,
-        triangle_y[1] as i32,
-        triangle_y[2] as i32,
-    ];
-
-    let a = triangle_area(xs[0], ys[0], xs[1], ys[1], xs[2], ys[2]);
-    let a1 = triangle_area(x, y, xs[1], ys[1], xs[2], ys[2]);
-    let a2 = triangle_area(xs[0], ys[0], x, y, xs[2], ys[2]);
-    let a3 = triangle_area(xs[1], ys[1], xs[2], ys[2], x, y);
-
-    let min_x = xs.into_iter().reduce(i32::min).unwrap();
-    let max_x = xs.into_iter().reduce(i32::max).unwrap();
-    let min_y = ys.into_iter().reduce(i32::min).unwrap();
-    let max_y = ys.into_iter().reduce(i32::max).unwrap();
-
-    a == a1 + a2 + a3 && x > min_x && x < max_x && y > min_y && y < max_y
+/// Check if an edge of the first polygon separates it from the second
+/// one: their projections on the normal of the edge don't overlap.
+fn has_separating_edge(polygon: &[Point], other: &[Point]) -> bool {
+    let project = |points: &[Point], normal: (i64, i64)| {
+        points
+            .iter()
+            .map(|p| p.x() as i64 * normal.0 + p.y() as i64 * normal.1)
+            .fold((i64::MAX, i64::MIN), |(min, max), v| (min.min(v), max.max(v)))
+    };
+    (0..polygon.len()).any(|i| {
+        let (start, end) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let normal = ((start.y() - end.y()) as i64, (end.x() - start.x()) as i64);
+        let (min, max) = project(polygon, normal);
+        let (other_min, other_max) = project(other, normal);
+        max < other_min || other_max < min
+    })
 }
 
-impl SpaceshipMovement for Spaceship {  
-    fn move_upward(&mut self) {
-        self.body.move_upward();
-        self.head.move_upward();
-        self.tail.move_upward();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+
+    /// The vertices of a square, in order.
+    fn square(x: i32, y: i32, size: i32) -> Vec<Point> {
+        vec![
+            Point::new(x, y),
+            Point::new(x + size, y),
+            Point::new(x + size, y + size),
+            Point::new(x, y + size),
+        ]
     }
 
-    fn move_downward(&mut self) {
-        self.body.move_downward();
-        self.head.move_downward();
-        self.tail.move_downward();
+    #[test]
+    fn polygons_apart_do_not_overlap() {
+        assert!(!do_polygons_overlap(&square(0, 0, 10), &square(20, 0, 10)));
+        assert!(!do_polygons_overlap(&square(0, 0, 10), &square(5, 11, 10)));
+        // The bounding boxes overlap, but the hypotenuse separates them.
+        let triangle = [Point::new(0, 0), Point::new(10, 0), Point::new(0, 10)];
+        assert!(!do_polygons_overlap(&triangle, &square(6, 6, 10)));
     }
-}
 
-impl SpaceshipMovement for SpaceshipHead {    
-    fn move_upward(&mut self) {
-        self.triangle_y = self.triangle_y.map(|v| v - SPACESHIP_SPEED as i16);
+    #[test]
+    fn touching_polygons_overlap() {
+        assert!(do_polygons_overlap(&square(0, 0, 10), &square(10, 0, 10)));
+        assert!(do_polygons_overlap(&square(0, 0, 10), &square(10, 10, 10)));
     }
 
-    fn move_downward(&mut self) {
-        self.triangle_y = self.triangle_y.map(|v| v + SPACESHIP_SPEED as i16);
+    #[test]
+    fn overlapping_polygons_overlap() {
+        assert!(do_polygons_overlap(&square(0, 0, 10), &square(5, 5, 10)));
+        assert!(do_polygons_overlap(&square(0, 0, 30), &square(10, 10, 5)));
     }
-}
 
-impl SpaceshipMovement for SpaceshipBody {    
-    fn move_upward(&mut self) {
-        self.rect.set_y(self.rect.y() - SPACESHIP_SPEED as i32);
-        self.porthole_1.1 -= SPACESHIP_SPEED as i16;
-        self.porthole_2.1 -= SPACESHIP_SPEED as i16;
+    #[test]
+    fn scaled_missile_collides_without_vertex_inside_spaceship() {
+        let config = GameConfig::default();
+        let spaceship = Spaceship::new(100, 300, &config.spaceship);
+        // A missile twenty times bigger than usual, whose body crosses the
+        // whole spaceship from left to right.
+        let missile = Missile {
+            position: Vector::new(-20.0, 305.0),
+            velocity: Vector::new(MISSILE_SPEED, 0.0),
+            size: 20.0,
+            head_color: config.missile.head_color,
+            body_color: config.missile.body_color,
+            tail_color: config.missile.tail_color,
+        };
+
+        let vertices = spaceship.polygons().concat();
+        let min_x = vertices.iter().map(|p| p.x()).min().unwrap();
+        let max_x = vertices.iter().map(|p| p.x()).max().unwrap();
+        let is_vertex_within = missile
+            .polygons()
+            .concat()
+            .iter()
+            .any(|p| min_x < p.x() && p.x() < max_x);
+        assert!(!is_vertex_within);
+        assert!(spaceship.collide_with(&missile));
     }
 
-    fn move_downward(&mut self) {
-        self.rect.set_y(self.rect.y() + SPACESHIP_SPEED as i32);
-        self.porthole_1.1 += SPACESHIP_SPEED as i16;
-        self.porthole_2.1 += SPACESHIP_SPEED as i16;
-    }
-}
+    #[test]
+    fn respawns_once_delay_passed() {
+        let config = GameConfig::default();
+        let mut clock = GameClock::new(Duration::from_millis(50));
+        let mut spaceship = Spaceship::new(0, 0, &config.spaceship);
+        spaceship.die(&clock);
 
-impl SpaceshipMovement for SpaceshipTail {   
-    fn move_upward(&mut self) {
-        self.left_triangle_y = self.left_triangle_y.map(|v| v - SPACESHIP_SPEED as i16);
-        self.right_triangle_y = self.right_triangle_y.map(|v| v - SPACESHIP_SPEED as i16);
-    }
-
-    fn move_downward(&mut self) {
-        self.left_triangle_y = self.left_triangle_y.map(|v| v + SPACESHIP_SPEED as i16);
-        self.right_triangle_y = self.right_triangle_y.map(|v| v + SPACESHIP_SPEED as i16);
+        // A 1s delay is over at the 20th tick.
+        for _ in 0..19 {
+            clock.tick();
+        }
+        assert!(!spaceship.can_respawn(&clock, Duration::from_secs(1)));
+        clock.tick();
+        assert!(spaceship.can_respawn(&clock, Duration::from_secs(1)));
     }
-}
\ No newline at end of file
+}
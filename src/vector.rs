@@ -0,0 +1,68 @@
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Mul;
+use std::ops::Sub;
+
+/// The **Vector** struct is a 2D vector with sub-pixel precision, used
+/// for the positions and velocities of the entities. The geometry drawn
+/// and tested for collisions is derived from it, rounded to the pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector {
+    /// The null vector.
+    pub const ZERO: Vector = Vector { x: 0.0, y: 0.0 };
+
+    pub fn new(x: f32, y: f32) -> Vector {
+        Vector { x, y }
+    }
+
+    /// The length of the vector.
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    /// The point at the given fraction (between 0 and 1) of the way from
+    /// this point to the other one.
+    pub fn lerp(&self, other: Vector, alpha: f32) -> Vector {
+        *self + (other - *self) * alpha
+    }
+
+    /// The coordinates rounded to the nearest pixel.
+    pub fn to_pixel(&self) -> (i32, i32) {
+        (self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: f32) -> Vector {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
@@ -1,179 +1,275 @@
 use crate::sdl2::gfx::primitives::DrawRenderer;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 
+use crate::config::WindowConfig;
+use crate::error::*;
+use crate::game_font::*;
+use crate::game_state::Player;
+use crate::pause::*;
 use crate::spaceship::*;
-use crate::GameFont;
+use crate::vector::Vector;
 use crate::GameState;
-use crate::WINDOW_HEIGHT;
-use crate::WINDOW_WIDTH;
 
 const COLOR_BACKGROUND: Color = Color::BLACK;
 
+/// The color drawn over the game while it is paused.
+const COLOR_PAUSE_OVERLAY: Color = Color::RGBA(0, 0, 0, 180);
+
+/// The color of the selected option in the pause menu.
+const COLOR_PAUSE_SELECTED: Color = Color::YELLOW;
+
+/// The font size of the scores (in points).
+const SCORE_FONT_SIZE: u16 = 28;
+
+/// The font sizes of the pause menu's title and options (in points).
+const PAUSE_TITLE_FONT_SIZE: u16 = 72;
+const PAUSE_OPTION_FONT_SIZE: u16 = 28;
+
+/// The height of an option in the pause menu (in pixels).
+const PAUSE_OPTION_HEIGHT: u32 = 40;
+
+/// The **Snapshot** struct keeps the positions of the entities at a tick
+/// of the simulation. The game is drawn between the previous tick and the
+/// current one, so the entities move smoothly whatever the tick rate.
+#[derive(Clone, Debug, Default)]
+pub struct Snapshot {
+    missiles: Vec<Vector>,
+    spaceships: [Option<Vector>; 2],
+}
+
+impl Snapshot {
+    /// Register the positions of the entities of the given game. A dead
+    /// **Spaceship** has no position.
+    pub fn capture(gs: &GameState) -> Snapshot {
+        let spaceship_position = |player| {
+            let spaceship = gs.spaceship(player);
+            spaceship.is_alive.then_some(spaceship.position)
+        };
+        Snapshot {
+            missiles: gs.missiles.iter().map(|m| m.position).collect(),
+            spaceships: [
+                spaceship_position(Player::One),
+                spaceship_position(Player::Two),
+            ],
+        }
+    }
+}
+
+/// The position an entity is drawn at, the given fraction of a tick after
+/// its previous position. An entity moving farther than its velocity in a
+/// single tick was teleported (wrapped around the screen, respawned...),
+/// so it is drawn at its current position.
+fn interpolate(previous: Option<Vector>, current: Vector, velocity: Vector, alpha: f32) -> Vector {
+    match previous {
+        Some(previous) if (current - previous).length() <= velocity.length() + f32::EPSILON => {
+            previous.lerp(current, alpha)
+        }
+        _ => current,
+    }
+}
+
 fn draw_background(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) {
     canvas.set_draw_color(COLOR_BACKGROUND);
     canvas.clear();
 }
 
-fn draw_missiles(canvas: &mut Canvas<Window>, gs: &GameState) {
-    for m in &gs.missiles {
+fn draw_missiles(
+    canvas: &mut Canvas<Window>,
+    gs: &GameState,
+    previous: &Snapshot,
+    alpha: f32,
+) -> GameResult<()> {
+    for (i, current) in gs.missiles.iter().enumerate() {
+        let mut m = current.clone();
+        m.position = interpolate(
+            previous.missiles.get(i).copied(),
+            current.position,
+            current.velocity,
+            alpha,
+        );
+        let (body, head, tail) = (m.body(), m.head(), m.tail());
+
         // draw body
-        canvas.set_draw_color(m.body.color);
+        canvas.set_draw_color(body.color);
         canvas
-            .fill_rect(m.body.rect)
-            .expect("Drawing failed for missile!");
+            .fill_rect(body.rect)
+            .map_err(|e| GameError::render("drawing failed for missile", e))?;
 
         // draw tail
-        canvas.set_draw_color(m.tail.color);
+        canvas.set_draw_color(tail.color);
         canvas
-            .filled_polygon(&m.tail.top_triangle_x, &m.tail.top_triangle_y, m.tail.color)
-            .expect("Drawing failed for top missile's tail!");
+            .filled_polygon(&tail.top_triangle_x, &tail.top_triangle_y, tail.color)
+            .map_err(|e| GameError::render("drawing failed for top missile's tail", e))?;
         canvas
-            .filled_polygon(&m.tail.bot_triangle_x, &m.tail.bot_triangle_y, m.tail.color)
-            .expect("Drawing failed for bottom missile's tail!");
+            .filled_polygon(&tail.bot_triangle_x, &tail.bot_triangle_y, tail.color)
+            .map_err(|e| GameError::render("drawing failed for bottom missile's tail", e))?;
 
         //draw head
-        canvas.set_draw_color(m.head.color);
+        canvas.set_draw_color(head.color);
         canvas
-            .filled_polygon(&m.head.triangle_x, &m.head.triangle_y, m.head.color)
-            .expect("Drawing failed for missile's head!");
+            .filled_polygon(&head.triangle_x, &head.triangle_y, head.color)
+            .map_err(|e| GameError::render("drawing failed for missile's head", e))?;
     }
+    Ok(())
 }
 
-fn draw_spaceship(canvas: &mut Canvas<Window>, spaceship: &Spaceship) {
+fn draw_spaceship(canvas: &mut Canvas<Window>, spaceship: &Spaceship) -> GameResult<()> {
+    let (body, head, tail) = (spaceship.body(), spaceship.head(), spaceship.tail());
     // body
-    canvas.set_draw_color(spaceship.body.body_color);
+    canvas.set_draw_color(body.body_color);
     canvas
-        .fill_rect(spaceship.body.rect)
-        .expect("Drawing failed for spaceship's body.");
+        .fill_rect(body.rect)
+        .map_err(|e| GameError::render("drawing failed for spaceship's body", e))?;
     // body's porthole #1
-    canvas.set_draw_color(spaceship.body.porthole_color);
+    canvas.set_draw_color(body.porthole_color);
     canvas
         .filled_circle(
-            spaceship.body.porthole_1.0,
-            spaceship.body.porthole_1.1,
-            spaceship.body.porthole_r,
-            spaceship.body.porthole_color,
+            body.porthole_1.0,
+            body.porthole_1.1,
+            body.porthole_r,
+            body.porthole_color,
         )
-        .expect("Drawing failed for spaceship's first porthole!");
+        .map_err(|e| GameError::render("drawing failed for spaceship's first porthole", e))?;
     // body's porthole #2
-    canvas.set_draw_color(spaceship.body.porthole_color);
+    canvas.set_draw_color(body.porthole_color);
     canvas
         .filled_circle(
-            spaceship.body.porthole_2.0,
-            spaceship.body.porthole_2.1,
-            spaceship.body.porthole_r,
-            spaceship.body.porthole_color,
+            body.porthole_2.0,
+            body.porthole_2.1,
+            body.porthole_r,
+            body.porthole_color,
         )
-        .expect("Drawing failed for spaceship's second porthole!");
+        .map_err(|e| GameError::render("drawing failed for spaceship's second porthole", e))?;
     // head
-    canvas.set_draw_color(spaceship.head.color);
+    canvas.set_draw_color(head.color);
     canvas
-        .filled_polygon(
-            &spaceship.head.triangle_x,
-            &spaceship.head.triangle_y,
-            spaceship.head.color,
-        )
-        .expect("Drawing failed for spaceship's head!");
+        .filled_polygon(&head.triangle_x, &head.triangle_y, head.color)
+        .map_err(|e| GameError::render("drawing failed for spaceship's head", e))?;
     // tail
-    canvas.set_draw_color(spaceship.tail.color);
+    canvas.set_draw_color(tail.color);
     canvas
-        .filled_polygon(
-            &spaceship.tail.left_triangle_x,
-            &spaceship.tail.left_triangle_y,
-            spaceship.tail.color,
-        )
-        .expect("Drawing failed for spaceship's left leg!");
+        .filled_polygon(&tail.left_triangle_x, &tail.left_triangle_y, tail.color)
+        .map_err(|e| GameError::render("drawing failed for spaceship's left leg", e))?;
     canvas
-        .filled_polygon(
-            &spaceship.tail.right_triangle_x,
-            &spaceship.tail.right_triangle_y,
-            spaceship.tail.color,
-        )
-        .expect("Drawing failed for spaceship's right leg!");
+        .filled_polygon(&tail.right_triangle_x, &tail.right_triangle_y, tail.color)
+        .map_err(|e| GameError::render("drawing failed for spaceship's right leg", e))?;
+    Ok(())
 }
 
-fn draw_score(canvas: &mut Canvas<Window>, gs: &GameState, gf: &GameFont) {
-    let texture_creator = canvas.texture_creator();
-
-    let font = gf.get_font(gf.schluber_path, 128);
-
-    let surface_p1 = gf.surface_from_str(&format!("{}", gs.score_p1), &font, Color::WHITE);
-
-    let surface_p2 = gf.surface_from_str(&format!("{}", gs.score_p2), &font, Color::WHITE);
-
+fn draw_score(canvas: &mut Canvas<Window>, gs: &GameState, gf: &GameFont) -> GameResult<()> {
+    let window = &gs.config.window;
     let screen_padding = 20;
-    let font_rect_width = 30;
-    let font_rect_height = 20;
-    let font_rect_p1 = Rect::new(
-        screen_padding as i32,
-        (WINDOW_HEIGHT - font_rect_height - screen_padding) as i32,
-        font_rect_width,
-        font_rect_height,
-    );
+    let bottom = (window.height - screen_padding) as i32;
+    let style = TextStyle::new(&gf.schluber, SCORE_FONT_SIZE, Color::WHITE);
 
-    let font_rect_p2 = Rect::new(
-        (WINDOW_WIDTH - font_rect_width - screen_padding) as i32,
-        (WINDOW_HEIGHT - font_rect_height - screen_padding) as i32,
-        font_rect_width,
-        font_rect_height,
-    );
+    let layout_p1 = TextLayout::new(screen_padding as i32, bottom, Anchor::BottomLeft);
+    gf.draw_text(canvas, &format!("{}", gs.score_p1), &style, &layout_p1)?;
 
-    let texture_p1 = texture_creator
-        .create_texture_from_surface(&surface_p1)
-        .expect("Failed to create texture from surface for p1!");
-
-    let texture_p2 = texture_creator
-        .create_texture_from_surface(&surface_p2)
-        .expect("Failed to create texture from surface for p2!");
-
-    canvas
-        .copy(&texture_p1, None, font_rect_p1)
-        .expect("Failed to copy p1 texture to canvas");
-    canvas
-        .copy(&texture_p2, None, font_rect_p2)
-        .expect("Failed to copy p2 texture to canvas");
+    let layout_p2 = TextLayout::new(
+        (window.width - screen_padding) as i32,
+        bottom,
+        Anchor::BottomRight,
+    );
+    gf.draw_text(canvas, &format!("{}", gs.score_p2), &style, &layout_p2)?;
+    Ok(())
 }
 
-fn draw_timeline(canvas: &mut Canvas<Window>, gs: &GameState) {
+fn draw_timeline(canvas: &mut Canvas<Window>, gs: &GameState) -> GameResult<()> {
+    let duration = gs.game_duration.as_millis() as u64;
+    let remaining = gs.game_duration.saturating_sub(gs.clock.elapsed()).as_millis() as u64;
     // Don't draw the line if the game time is over.
-    if gs.game_duration.as_secs() < gs.starting_time.elapsed().as_secs() {
-        return;
+    if remaining == 0 {
+        return Ok(());
     }
 
+    let window = &gs.config.window;
     let line_width = 10;
-    let line_increment = WINDOW_HEIGHT / gs.game_duration.as_secs() as u32;
-    let line_height =
-        line_increment * (gs.game_duration.as_secs() - gs.starting_time.elapsed().as_secs()) as u32;
+    let line_height = (window.height as u64 * remaining / duration) as u32;
     let timeline_rect = Rect::new(
-        (WINDOW_WIDTH / 2 - line_width / 2) as i32,
-        (WINDOW_HEIGHT - line_height) as i32,
+        (window.width / 2 - line_width / 2) as i32,
+        (window.height - line_height) as i32,
         line_width,
         line_height,
     );
-    let rgb_value = (255 * (gs.game_duration.as_secs() - gs.starting_time.elapsed().as_secs())
-        / gs.game_duration.as_secs()) as u8;
+    let rgb_value = (255 * remaining / duration) as u8;
     canvas.set_draw_color(Color::RGB(255, rgb_value, rgb_value));
     canvas
         .fill_rect(timeline_rect)
-        .expect("Drawing failed for timeline!");
+        .map_err(|e| GameError::render("drawing failed for timeline", e))?;
+    Ok(())
 }
 
-/// Draw the game according to a provided GameState parameter.
-pub fn draw_game(canvas: &mut Canvas<Window>, gs: &GameState, gf: &GameFont) {
-    draw_background(canvas);
+/// Draw the pause menu over the frozen game, darkening it.
+pub fn draw_pause_menu(
+    canvas: &mut Canvas<Window>,
+    window: &WindowConfig,
+    pause_menu: &PauseMenu,
+    gf: &GameFont,
+) -> GameResult<()> {
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(COLOR_PAUSE_OVERLAY);
+    canvas
+        .fill_rect(None)
+        .map_err(|e| GameError::render("drawing failed for pause overlay", e))?;
+    canvas.set_blend_mode(BlendMode::None);
+
+    let title_style = TextStyle::new(&gf.poetsen, PAUSE_TITLE_FONT_SIZE, Color::WHITE);
+    let title_layout = TextLayout::new(
+        (window.width / 2) as i32,
+        (window.height / 4) as i32,
+        Anchor::Top,
+    );
+    let rect_title = gf.draw_text(canvas, "PAUSE", &title_style, &title_layout)?;
 
-    if gs.spaceship_p1.is_alive {
-        draw_spaceship(canvas, &gs.spaceship_p1);
+    for (i, option) in PAUSE_OPTIONS.iter().enumerate() {
+        let color = if i == pause_menu.selected {
+            COLOR_PAUSE_SELECTED
+        } else {
+            Color::WHITE
+        };
+        let option_style = TextStyle::new(&gf.poetsen, PAUSE_OPTION_FONT_SIZE, color);
+        let option_layout = TextLayout::new(
+            (window.width / 2) as i32,
+            rect_title.bottom() + (i as u32 * PAUSE_OPTION_HEIGHT) as i32,
+            Anchor::Top,
+        );
+        gf.draw_text(canvas, option.label(), &option_style, &option_layout)?;
     }
-    if gs.spaceship_p2.is_alive {
-        draw_spaceship(canvas, &gs.spaceship_p2);
+    Ok(())
+}
+
+/// Draw the game according to a provided GameState parameter, the given
+/// fraction of a tick (between 0 and 1) after the previous positions of
+/// its entities. The canvas is not presented.
+pub fn draw_game(
+    canvas: &mut Canvas<Window>,
+    gs: &GameState,
+    previous: &Snapshot,
+    alpha: f32,
+    gf: &GameFont,
+) -> GameResult<()> {
+    draw_background(canvas);
+
+    for (i, player) in [Player::One, Player::Two].into_iter().enumerate() {
+        let current = gs.spaceship(player);
+        if !current.is_alive {
+            continue;
+        }
+        let mut spaceship = current.clone();
+        spaceship.position = interpolate(
+            previous.spaceships[i],
+            current.position,
+            current.velocity,
+            alpha,
+        );
+        draw_spaceship(canvas, &spaceship)?;
     }
 
-    draw_missiles(canvas, gs);
-    draw_score(canvas, gs, gf);
-    draw_timeline(canvas, gs);
-    canvas.present();
+    draw_missiles(canvas, gs, previous, alpha)?;
+    draw_score(canvas, gs, gf)?;
+    draw_timeline(canvas, gs)
 }